@@ -1,4 +1,4 @@
-use crab_claw::{triangle_to_vertex, vertex_to_triangle, Triangle};
+use crab_claw::{triangle_to_vertex, vertex_to_triangle, PosNormalVertex as Vertex, Triangle};
 use genmesh::Triangulate;
 use genmesh::Vertices;
 use std::{fs, io::Write, mem};
@@ -6,12 +6,14 @@ use std::{fs, io::Write, mem};
 fn main() {
     let cone = genmesh::generators::IcoSphere::subdivide(3);
     let f: Vec<Triangle<Vertex>> =
-        vertex_to_triangle(cone.triangulate().vertices().map(Into::into)).collect();
+        vertex_to_triangle(cone.triangulate().vertices().map(from_genmesh)).collect();
     let s = crab_claw::slice_convex(
         f,
         crab_claw::Plane::from_pos_normal([0.0; 3], [1.0, 0.2, 0.0]),
         Default::default(),
+        Default::default(),
     )
+    .sliced()
     .unwrap();
 
     export("upper", triangle_to_vertex(s.0.hull));
@@ -20,35 +22,10 @@ fn main() {
     export("lowerc", triangle_to_vertex(s.1.cross_section));
 }
 
-#[derive(Clone)]
-struct Vertex {
-    position: [f32; 3],
-    normal: [f32; 3],
-}
-
-impl crab_claw::Vertex for Vertex {
-    fn new_interpolated(a: &Self, b: &Self, t: f32) -> Self {
-        Vertex {
-            position: crab_claw::lerp3(a.pos(), b.pos(), t),
-            normal: crab_claw::lerp3(a.normal, b.normal, t),
-        }
-    }
-
-    fn new(position: [f32; 3], _: [f32; 2], normal: [f32; 3]) -> Self {
-        Vertex { position, normal }
-    }
-
-    fn pos(&self) -> [f32; 3] {
-        self.position
-    }
-}
-
-impl From<genmesh::Vertex> for Vertex {
-    fn from(g: genmesh::Vertex) -> Self {
-        Vertex {
-            position: g.pos.into(),
-            normal: g.normal.into(),
-        }
+fn from_genmesh(g: genmesh::Vertex) -> Vertex {
+    Vertex {
+        pos: g.pos.into(),
+        normal: g.normal.into(),
     }
 }
 
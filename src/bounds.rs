@@ -0,0 +1,63 @@
+use crate::math::{magnitude, sub_v3};
+use crate::options::Tolerance;
+use crate::{Plane, Scalar, SubMesh, Triangle, Vertex};
+
+/// Axis aligned bounding box.
+#[derive(Copy, Clone)]
+pub(crate) struct Aabb<S> {
+    pub min: [S; 3],
+    pub max: [S; 3],
+}
+
+impl<S: Scalar> Aabb<S> {
+    pub(crate) fn of_points(points: impl IntoIterator<Item = [S; 3]>) -> Self {
+        let mut min = [S::MAX; 3];
+        let mut max = [S::MIN; 3];
+        for pos in points {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(pos[axis]);
+                max[axis] = max[axis].max(pos[axis]);
+            }
+        }
+        Aabb { min, max }
+    }
+
+    pub(crate) fn of_triangles<'a, V: Vertex<S> + 'a>(
+        triangles: impl IntoIterator<Item = &'a Triangle<V>>,
+    ) -> Self {
+        Aabb::of_points(
+            triangles
+                .into_iter()
+                .flat_map(|t| IntoIterator::into_iter([t.a.pos(), t.b.pos(), t.c.pos()])),
+        )
+    }
+
+    pub(crate) fn of<V: Vertex<S>>(mesh: &SubMesh<V>) -> Self {
+        Aabb::of_triangles(mesh.hull.iter().chain(&mesh.cross_section))
+    }
+
+    /// Length of the diagonal, zero for an empty box.
+    pub(crate) fn diagonal(&self) -> S {
+        if self.min[0] > self.max[0] {
+            S::ZERO
+        } else {
+            magnitude(sub_v3(self.max, self.min))
+        }
+    }
+
+    /// Whether the plane passes through the box.
+    ///
+    /// The corners are measured like the vertices are classified, exactly with the `robust` feature.
+    pub(crate) fn intersects(&self, plane: Plane<S>, tolerance: Tolerance<S>) -> bool {
+        let normal = plane.normal();
+        let mut near = self.min;
+        let mut far = self.max;
+        for axis in 0..3 {
+            if normal[axis] < S::ZERO {
+                std::mem::swap(&mut near[axis], &mut far[axis]);
+            }
+        }
+        plane.signed_distance(near) < -tolerance.on_plane()
+            && plane.signed_distance(far) > tolerance.on_plane()
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use crate::math::{dot_v3, lerp3, magnitude_squared, pos_key, sub_v3};
+use crate::{Scalar, SubMesh, Triangle, Vertex};
+
+fn find(parents: &mut [usize], mut idx: usize) -> usize {
+    while parents[idx] != idx {
+        parents[idx] = parents[parents[idx]];
+        idx = parents[idx];
+    }
+    idx
+}
+
+/// Splits the hull into its connected pieces and hands every cap to the piece it closes.
+///
+/// Triangles are considered connected if they share a vertex position.
+pub(crate) fn split_components<S: Scalar, V: Vertex<S>>(
+    hull: Vec<Triangle<V>>,
+    caps: Vec<Vec<Triangle<V>>>,
+) -> Vec<SubMesh<V>> {
+    let mut parents = (0..hull.len()).collect::<Vec<_>>();
+    let mut first_seen = HashMap::with_capacity(hull.len() * 3);
+    for (idx, triangle) in hull.iter().enumerate() {
+        for pos in [triangle.a.pos(), triangle.b.pos(), triangle.c.pos()].iter() {
+            let other = *first_seen.entry(pos_key(*pos)).or_insert(idx);
+            let (root, other_root) = (find(&mut parents, idx), find(&mut parents, other));
+            parents[root] = other_root;
+        }
+    }
+
+    let mut component_of_root = HashMap::new();
+    let mut sub_meshes: Vec<SubMesh<V>> = vec![];
+    let mut components = Vec::with_capacity(hull.len());
+    for (idx, triangle) in hull.into_iter().enumerate() {
+        let root = find(&mut parents, idx);
+        let component = *component_of_root.entry(root).or_insert_with(|| {
+            sub_meshes.push(SubMesh {
+                hull: vec![],
+                cross_section: vec![],
+            });
+            sub_meshes.len() - 1
+        });
+        components.push(component);
+        sub_meshes[component].hull.push(triangle);
+    }
+
+    for cap in caps {
+        let component = cap.first().and_then(|triangle| {
+            let pos = triangle.a.pos();
+            match first_seen.get(&pos_key(pos)) {
+                Some(&idx) => Some(components[idx]),
+                // welding the outline may have moved the cap off the vertices of the hull
+                None => closest_component(&sub_meshes, pos),
+            }
+        });
+        if let Some(component) = component {
+            sub_meshes[component].cross_section.extend(cap);
+        }
+    }
+
+    sub_meshes
+}
+
+/// Returns the index of the sub mesh whose hull has the edge closest to `pos`.
+fn closest_component<S: Scalar, V: Vertex<S>>(
+    sub_meshes: &[SubMesh<V>],
+    pos: [S; 3],
+) -> Option<usize> {
+    let mut closest = None;
+    for (component, sub_mesh) in sub_meshes.iter().enumerate() {
+        for triangle in &sub_mesh.hull {
+            let [a, b, c] = [triangle.a.pos(), triangle.b.pos(), triangle.c.pos()];
+            for &(from, to) in &[(a, b), (b, c), (c, a)] {
+                let distance = segment_distance_squared(pos, from, to);
+                if closest.is_none_or(|(_, closest)| distance < closest) {
+                    closest = Some((component, distance));
+                }
+            }
+        }
+    }
+    closest.map(|(component, _)| component)
+}
+
+fn segment_distance_squared<S: Scalar>(pos: [S; 3], from: [S; 3], to: [S; 3]) -> S {
+    let edge = sub_v3(to, from);
+    let len = magnitude_squared(edge);
+    let t = if len > S::ZERO {
+        (dot_v3(sub_v3(pos, from), edge) / len)
+            .max(S::ZERO)
+            .min(S::ONE)
+    } else {
+        S::ZERO
+    };
+    magnitude_squared(sub_v3(pos, lerp3(from, to, t)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_components;
+    use crate::testing::V;
+    use crate::Triangle;
+
+    fn triangle(offset: f32) -> Triangle<V> {
+        let v = |x: f32, y: f32| V {
+            pos: [x + offset, y, 0.0],
+            normal: [0.0, 0.0, 1.0],
+            uv: [0.0, 0.0],
+        };
+        Triangle {
+            a: v(0.0, 0.0),
+            b: v(1.0, 0.0),
+            c: v(0.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn cap_off_the_hull_vertices() {
+        let hull = vec![triangle(0.0), triangle(10.0)];
+        let caps = vec![vec![triangle(10.001)], vec![triangle(0.0)]];
+        let sub_meshes = split_components(hull, caps);
+        assert_eq!(sub_meshes.len(), 2);
+        assert_eq!(sub_meshes[0].cross_section.len(), 1);
+        assert_eq!(sub_meshes[0].cross_section[0].a.pos, [0.0, 0.0, 0.0]);
+        assert_eq!(sub_meshes[1].cross_section.len(), 1);
+        assert_eq!(sub_meshes[1].cross_section[0].a.pos, [10.001, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::math::{cross, dot_v3};
+use crate::{Plane, Scalar, Vertex};
+
+/// The attributes the slicer computes for a vertex of the cross section.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CapAttributes<S = f32> {
+    /// The texture coordinates within the [`TextureBounds`](crate::TextureBounds) of the slice.
+    pub uv: [S; 2],
+    /// The plane normal for the upper cap and its negation for the lower one.
+    pub normal: [S; 3],
+    /// The unit vector in the plane along which the u texture coordinate grows.
+    pub tangent: [S; 3],
+    /// The unit vector in the plane along which the v texture coordinate grows.
+    pub bitangent: [S; 3],
+}
+
+impl<S: Scalar> CapAttributes<S> {
+    /// Returns the sign of the bitangent relative to `cross(normal, tangent)`, as stored in the `w`
+    /// component of a glTF tangent.
+    ///
+    /// Since both caps share their texture coordinates but not their normal, the upper and lower cap
+    /// have opposing handedness.
+    #[inline]
+    pub fn handedness(&self) -> S {
+        if dot_v3(cross(self.normal, self.tangent), self.bitangent) < S::ZERO {
+            -S::ONE
+        } else {
+            S::ONE
+        }
+    }
+}
+
+/// Creates the vertices of a sliced mesh, as a stateful replacement of the associated functions of
+/// [`Vertex`].
+///
+/// This allows writing the new vertices straight into external buffers, counting them or looking up
+/// per mesh data while slicing.
+pub trait VertexConstructor<V, S: Scalar = f32> {
+    /// Creates a vertex from the given position, uv and normal, see [`Vertex::new`].
+    fn vertex(&mut self, pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> V;
+
+    /// Creates a vertex between the two given ones, see [`Vertex::new_interpolated`].
+    fn interpolated(&mut self, a: &V, b: &V, t: S) -> V;
+
+    /// Creates the cap vertex for `cut`, a vertex on the outline of the cut.
+    ///
+    /// Unlike [`Vertex::new`] this has access to the vertex the cap vertex is made for, so the caps
+    /// can inherit attributes like colours, bone weights or material ids from the mesh that was cut.
+    /// `cut` is either a vertex created by [`VertexConstructor::interpolated`] or a vertex of the mesh
+    /// lying on the plane. This is called once per cap vertex and side.
+    ///
+    /// By default this passes the position of `cut` on to [`VertexConstructor::vertex`], dropping the
+    /// tangent frame.
+    #[inline]
+    fn cap_vertex(&mut self, cut: &V, plane: Plane<S>, attributes: CapAttributes<S>) -> V
+    where
+        V: Vertex<S>,
+    {
+        let _ = plane;
+        self.vertex(cut.pos(), attributes.uv, attributes.normal)
+    }
+
+    /// Returns a constructor to split triangles with on another thread.
+    ///
+    /// With the `parallel` feature, large meshes are split in chunks across threads if this returns
+    /// a constructor, each chunk getting its own. The vertices are then created out of order, but end
+    /// up in the same place as without the feature. By default this returns `None`, which keeps all
+    /// vertex creation on the calling thread.
+    #[inline]
+    fn fork(&self) -> Option<Box<dyn VertexConstructor<V, S> + Send>> {
+        None
+    }
+}
+
+impl<V, S: Scalar, VB: VertexConstructor<V, S> + ?Sized> VertexConstructor<V, S> for &mut VB {
+    #[inline]
+    fn vertex(&mut self, pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> V {
+        VB::vertex(self, pos, uv, normal)
+    }
+
+    #[inline]
+    fn interpolated(&mut self, a: &V, b: &V, t: S) -> V {
+        VB::interpolated(self, a, b, t)
+    }
+
+    #[inline]
+    fn cap_vertex(&mut self, cut: &V, plane: Plane<S>, attributes: CapAttributes<S>) -> V
+    where
+        V: Vertex<S>,
+    {
+        VB::cap_vertex(self, cut, plane, attributes)
+    }
+
+    #[inline]
+    fn fork(&self) -> Option<Box<dyn VertexConstructor<V, S> + Send>> {
+        VB::fork(self)
+    }
+}
+
+impl<V, S: Scalar, VB: VertexConstructor<V, S> + ?Sized> VertexConstructor<V, S> for Box<VB> {
+    #[inline]
+    fn vertex(&mut self, pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> V {
+        VB::vertex(self, pos, uv, normal)
+    }
+
+    #[inline]
+    fn interpolated(&mut self, a: &V, b: &V, t: S) -> V {
+        VB::interpolated(self, a, b, t)
+    }
+
+    #[inline]
+    fn cap_vertex(&mut self, cut: &V, plane: Plane<S>, attributes: CapAttributes<S>) -> V
+    where
+        V: Vertex<S>,
+    {
+        VB::cap_vertex(self, cut, plane, attributes)
+    }
+
+    #[inline]
+    fn fork(&self) -> Option<Box<dyn VertexConstructor<V, S> + Send>> {
+        VB::fork(self)
+    }
+}
+
+/// Creates the vertices with the associated functions of [`Vertex`].
+pub(crate) struct NewVertex;
+
+impl<S: Scalar, V: Vertex<S>> VertexConstructor<V, S> for NewVertex {
+    #[inline]
+    fn vertex(&mut self, pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> V {
+        V::new(pos, uv, normal)
+    }
+
+    #[inline]
+    fn interpolated(&mut self, a: &V, b: &V, t: S) -> V {
+        V::new_interpolated(a, b, t)
+    }
+
+    #[inline]
+    fn cap_vertex(&mut self, cut: &V, _: Plane<S>, attributes: CapAttributes<S>) -> V {
+        V::new_cap(cut.pos(), attributes)
+    }
+
+    #[inline]
+    fn fork(&self) -> Option<Box<dyn VertexConstructor<V, S> + Send>> {
+        Some(Box::new(NewVertex))
+    }
+}
+
+/// Builds the vertices of the cross section with a closure taking the same arguments as
+/// [`VertexConstructor::cap_vertex`], creating all other vertices with the associated functions of
+/// [`Vertex`].
+pub struct CapConstructor<F>(pub F);
+
+impl<S, V, F> VertexConstructor<V, S> for CapConstructor<F>
+where
+    S: Scalar,
+    V: Vertex<S>,
+    F: FnMut(&V, Plane<S>, CapAttributes<S>) -> V,
+{
+    #[inline]
+    fn vertex(&mut self, pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> V {
+        V::new(pos, uv, normal)
+    }
+
+    #[inline]
+    fn interpolated(&mut self, a: &V, b: &V, t: S) -> V {
+        V::new_interpolated(a, b, t)
+    }
+
+    #[inline]
+    fn cap_vertex(&mut self, cut: &V, plane: Plane<S>, attributes: CapAttributes<S>) -> V {
+        (self.0)(cut, plane, attributes)
+    }
+}
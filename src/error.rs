@@ -0,0 +1,45 @@
+use std::fmt;
+
+/// Errors reported by the fallible slicing functions for malformed or degenerate input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SliceError {
+    /// The number of vertices or indices is not a multiple of three, holding the number of trailing
+    /// ones.
+    IncompleteTriangle { trailing: usize },
+    /// An index of the triangle at the given index lies outside of the vertices.
+    IndexOutOfRange { triangle: usize },
+    /// A vertex position of the triangle at the given index is NaN or infinite.
+    NonFinitePosition { triangle: usize },
+    /// The normal or distance of the plane is NaN or infinite.
+    NonFinitePlane,
+    /// The plane normal has zero length, this happens when constructing a plane from collinear points.
+    ZeroLengthNormal,
+    /// The outline of the cut could not be triangulated, usually because the mesh isnt closed.
+    DegenerateCap,
+    /// The transform of the mesh has no inverse or isnt finite.
+    SingularTransform,
+}
+
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SliceError::IncompleteTriangle { trailing } => write!(
+                f,
+                "vertex or index count is not a multiple of three, {} are left over",
+                trailing
+            ),
+            SliceError::IndexOutOfRange { triangle } => {
+                write!(f, "triangle {} has an index out of range", triangle)
+            }
+            SliceError::NonFinitePosition { triangle } => {
+                write!(f, "triangle {} has a non-finite vertex position", triangle)
+            }
+            SliceError::NonFinitePlane => write!(f, "plane has a non-finite normal or distance"),
+            SliceError::ZeroLengthNormal => write!(f, "plane normal has zero length"),
+            SliceError::DegenerateCap => write!(f, "cross section could not be triangulated"),
+            SliceError::SingularTransform => write!(f, "transform is not invertible"),
+        }
+    }
+}
+
+impl std::error::Error for SliceError {}
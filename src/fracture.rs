@@ -0,0 +1,228 @@
+use crate::bounds::Aabb;
+use crate::constructor::NewVertex;
+use crate::math::{dot_v3, lerp3, normalized, sub_v3};
+use crate::multi::slice_cell;
+use crate::options::Tolerance;
+use crate::plane::Side;
+use crate::{
+    Plane, Scalar, SliceOptions, SubMesh, TextureBounds, Triangle, Vertex, VertexConstructor,
+};
+
+/// Breaks a convex mesh into one cell per seed point.
+///
+/// Every cell is the part of the mesh closer to its seed than to any other, cut out along the
+/// bisecting planes between the seeds. Seeds whose cell lies outside of the mesh yield no cell, nor
+/// do seeds repeating the position of an earlier one.
+pub fn fracture_voronoi<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    seeds: &[[S; 3]],
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> Vec<SubMesh<V>> {
+    fracture_voronoi_with(triangles, seeds, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`fracture_voronoi`], but creates the new vertices with the given constructor.
+pub fn fracture_voronoi_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    seeds: &[[S; 3]],
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Vec<SubMesh<V>> {
+    let mesh = SubMesh {
+        hull: triangles.into_iter().collect::<Vec<_>>(),
+        cross_section: vec![],
+    };
+    if mesh.hull.is_empty() {
+        return vec![];
+    }
+    let mesh_aabb = Aabb::of(&mesh);
+    let tolerance = options.resolve_extent(mesh_aabb.diagonal());
+
+    let mut cells = Vec::with_capacity(seeds.len());
+    for (i, &seed) in seeds.iter().enumerate() {
+        // the first of several seeds at the same position gets the whole cell
+        if seeds[..i].contains(&seed) {
+            continue;
+        }
+        // cutting along the closest neighbours first shrinks the cell quickly, letting the bounding
+        // box test skip most of the remaining planes
+        let mut neighbours = seeds
+            .iter()
+            .copied()
+            .filter(|&other| other != seed)
+            .collect::<Vec<_>>();
+        neighbours.sort_by(|&a, &b| {
+            let (da, db) = (sub_v3(a, seed), sub_v3(b, seed));
+            dot_v3(da, da)
+                .partial_cmp(&dot_v3(db, db))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut cell = Some((mesh_aabb, mesh.clone()));
+        for other in neighbours {
+            let (aabb, current) = match cell {
+                Some(cell) => cell,
+                None => break,
+            };
+            // the normal points towards the seed, so its cell lies above the bisector
+            let plane = Plane::from_pos_normal(
+                lerp3(seed, other, S::from_f64(0.5)),
+                normalized(sub_v3(seed, other)),
+            );
+            cell = if aabb.intersects(plane, tolerance) {
+                match slice_cell(current, plane, &texture_bounds, tolerance, ctor) {
+                    Ok((upper, _)) => Some((Aabb::of(&upper), upper)),
+                    Err(current) => keep_if_above(plane, aabb, current, tolerance),
+                }
+            } else {
+                keep_if_above(plane, aabb, current, tolerance)
+            };
+        }
+        if let Some((_, cell)) = cell {
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+/// Keeps a cell that was not cut by the plane if it lies above it.
+fn keep_if_above<S: Scalar, V: Vertex<S>>(
+    plane: Plane<S>,
+    aabb: Aabb<S>,
+    cell: SubMesh<V>,
+    tolerance: Tolerance<S>,
+) -> Option<(Aabb<S>, SubMesh<V>)> {
+    // the cell lies on one side of the plane, so the first vertex not on the plane tells which
+    let side = cell
+        .hull
+        .iter()
+        .chain(&cell.cross_section)
+        .flat_map(|t| vec![t.a.pos(), t.b.pos(), t.c.pos()])
+        .map(|pos| plane.classify_side_within(pos, tolerance))
+        .find(|&side| side != Side::On);
+    if side == Some(Side::Below) {
+        None
+    } else {
+        Some((aabb, cell))
+    }
+}
+
+/// A small deterministic random number generator for seed points.
+///
+/// This is splitmix64, which is plenty for scattering points but not suitable for anything else.
+#[derive(Clone, Debug)]
+pub struct SeedRng {
+    state: u64,
+}
+
+impl SeedRng {
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        SeedRng { state: seed }
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in the range [0;1).
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Like [`SeedRng::next_f32`], converted to the given scalar type.
+    #[inline]
+    fn next_scalar<S: Scalar>(&mut self) -> S {
+        S::from_f64(self.next_f32().into())
+    }
+}
+
+/// Generates `count` seed points uniformly distributed in the bounding box of the mesh.
+pub fn random_seeds<S: Scalar, V: Vertex<S>>(
+    triangles: &[Triangle<V>],
+    count: usize,
+    rng: &mut SeedRng,
+) -> Vec<[S; 3]> {
+    let Aabb { min, max } = Aabb::of_triangles(triangles);
+    (0..count)
+        .map(|_| {
+            let mut point = [S::ZERO; 3];
+            for axis in 0..3 {
+                point[axis] = min[axis] + rng.next_scalar::<S>() * (max[axis] - min[axis]);
+            }
+            point
+        })
+        .collect()
+}
+
+/// Generates `count` seed points clustered around `center`, useful for concentrating small chunks
+/// around an impact point.
+///
+/// The points fall off with the distance to the center, most of them lie within `radius` of it. Points
+/// are clamped to the bounding box of the mesh.
+pub fn clustered_seeds<S: Scalar, V: Vertex<S>>(
+    triangles: &[Triangle<V>],
+    count: usize,
+    center: [S; 3],
+    radius: S,
+    rng: &mut SeedRng,
+) -> Vec<[S; 3]> {
+    let Aabb { min, max } = Aabb::of_triangles(triangles);
+    (0..count)
+        .map(|_| {
+            // pick a random direction by rejection sampling the unit ball
+            let direction = loop {
+                let (one, two) = (S::ONE, S::from_f64(2.0));
+                let v = [
+                    rng.next_scalar::<S>() * two - one,
+                    rng.next_scalar::<S>() * two - one,
+                    rng.next_scalar::<S>() * two - one,
+                ];
+                let len = dot_v3(v, v);
+                if len > S::from_f64(1e-6) && len <= one {
+                    break normalized(v);
+                }
+            };
+            // squaring the random distance biases the points towards the center
+            let dist: S = rng.next_scalar();
+            let dist = dist * dist * radius;
+            let mut point = [S::ZERO; 3];
+            for axis in 0..3 {
+                point[axis] = (center[axis] + direction[axis] * dist)
+                    .max(min[axis])
+                    .min(max[axis]);
+            }
+            point
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_close, cube, volume};
+    use crate::{fracture_voronoi, SliceOptions, TextureBounds};
+
+    #[test]
+    fn duplicate_seeds() {
+        let seeds = [[0.5, 0.0, 0.0], [-0.5, 0.0, 0.0], [0.5, 0.0, 0.0]];
+        let cells = fracture_voronoi(
+            cube(),
+            &seeds,
+            TextureBounds::default(),
+            SliceOptions::default(),
+        );
+        assert_eq!(cells.len(), 2);
+        assert_close(cells.iter().map(volume).sum(), 8.0);
+        for cell in &cells {
+            assert_close(volume(cell), 4.0);
+        }
+    }
+}
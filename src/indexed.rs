@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+
+use crate::bounds::Aabb;
+use crate::constructor::NewVertex;
+use crate::math::negate_v3;
+use crate::plane::Side;
+use crate::triangulate::{triangulate_loops, Cap};
+use crate::{
+    coplanar_side, is_degenerate, unsliced_outcome, CapAttributes, Plane, Scalar, SliceError,
+    SliceOptions, SliceOutcome, TextureBounds, Triangle, Vertex, VertexConstructor,
+};
+
+/// A mesh given as a vertex buffer and a triangle list index buffer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexedMesh<V> {
+    pub vertices: Vec<V>,
+    pub indices: Vec<u32>,
+}
+
+impl<V> IndexedMesh<V> {
+    #[inline]
+    pub fn new(vertices: Vec<V>, indices: Vec<u32>) -> Self {
+        IndexedMesh { vertices, indices }
+    }
+
+    /// Iterates over the triangles of this mesh, cloning their vertices.
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle<V>> + '_
+    where
+        V: Clone,
+    {
+        self.indices.chunks_exact(3).map(move |idx| Triangle {
+            a: self.vertices[idx[0] as usize].clone(),
+            b: self.vertices[idx[1] as usize].clone(),
+            c: self.vertices[idx[2] as usize].clone(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexedSubMesh<V> {
+    pub hull: IndexedMesh<V>,
+    pub cross_section: IndexedMesh<V>,
+}
+
+/// The outcome of slicing an indexed mesh with [`slice_indexed`].
+pub enum IndexedSliceResult<V> {
+    /// The plane cut through the mesh, holding the upper and the lower half.
+    Sliced(IndexedSubMesh<V>, IndexedSubMesh<V>),
+    /// The plane didnt cut through the mesh, telling where it lies instead. This is never
+    /// [`SliceOutcome::Sliced`].
+    Unsliced(SliceOutcome),
+}
+
+impl<V> IndexedSliceResult<V> {
+    /// Returns the upper and lower half if the mesh was sliced.
+    #[inline]
+    pub fn sliced(self) -> Option<(IndexedSubMesh<V>, IndexedSubMesh<V>)> {
+        match self {
+            IndexedSliceResult::Sliced(upper, lower) => Some((upper, lower)),
+            IndexedSliceResult::Unsliced(_) => None,
+        }
+    }
+
+    /// Returns where the mesh lies relative to the plane, without the halves.
+    #[inline]
+    pub fn outcome(&self) -> SliceOutcome {
+        match self {
+            IndexedSliceResult::Sliced(..) => SliceOutcome::Sliced,
+            IndexedSliceResult::Unsliced(outcome) => *outcome,
+        }
+    }
+}
+
+/// The vertices of a mesh being sliced, the original ones followed by the ones created on cut edges.
+struct CutVertices<'a, V> {
+    original: &'a [V],
+    cut: Vec<V>,
+    /// maps an undirected edge to the index of the vertex cutting it
+    edges: HashMap<(u32, u32), u32>,
+}
+
+impl<V> CutVertices<'_, V> {
+    fn get(&self, idx: u32) -> &V {
+        let idx = idx as usize;
+        match self.original.get(idx) {
+            Some(v) => v,
+            None => &self.cut[idx - self.original.len()],
+        }
+    }
+
+    /// Returns the vertex where the plane cuts the edge, creating it if the edge wasnt cut before.
+    fn cut_edge<S: Scalar>(
+        &mut self,
+        plane: Plane<S>,
+        ctor: &mut impl VertexConstructor<V, S>,
+        a: u32,
+        b: u32,
+    ) -> u32
+    where
+        V: Vertex<S>,
+    {
+        let (a, b) = (a.min(b), a.max(b));
+        let CutVertices {
+            original,
+            cut,
+            edges,
+        } = self;
+        *edges.entry((a, b)).or_insert_with(|| {
+            let (va, vb) = (&original[a as usize], &original[b as usize]);
+            let t = plane.intersection_factor(va.pos(), vb.pos());
+            cut.push(ctor.interpolated(va, vb, t));
+            (original.len() + cut.len() - 1) as u32
+        })
+    }
+}
+
+/// Slices an indexed mesh, keeping shared vertices shared.
+///
+/// Each edge crossing the plane is only cut once, no matter how many triangles share it. The
+/// resulting hulls only contain the vertices they reference. A mesh the plane doesnt cut is left
+/// as it is, the result only tells where it lies.
+///
+/// Fails if the number of indices isnt a multiple of three or an index lies outside of the vertices.
+pub fn slice_indexed<S: Scalar, V: Vertex<S> + Clone>(
+    mesh: &IndexedMesh<V>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> Result<IndexedSliceResult<V>, SliceError> {
+    slice_indexed_with(mesh, plane, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`slice_indexed`], but creates the new vertices with the given constructor.
+pub fn slice_indexed_with<S: Scalar, V: Vertex<S> + Clone>(
+    mesh: &IndexedMesh<V>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Result<IndexedSliceResult<V>, SliceError> {
+    let trailing = mesh.indices.len() % 3;
+    if trailing != 0 {
+        return Err(SliceError::IncompleteTriangle { trailing });
+    }
+    if let Some(idx) = mesh
+        .indices
+        .iter()
+        .position(|&idx| idx as usize >= mesh.vertices.len())
+    {
+        return Err(SliceError::IndexOutOfRange { triangle: idx / 3 });
+    }
+
+    let extent = Aabb::of_points(mesh.vertices.iter().map(V::pos)).diagonal();
+    let tolerance = options.resolve_extent(extent);
+    let sides = mesh
+        .vertices
+        .iter()
+        .map(|v| plane.classify_side_within(v.pos(), tolerance))
+        .collect::<Vec<_>>();
+    let mut vertices = CutVertices {
+        original: &mesh.vertices,
+        cut: vec![],
+        edges: HashMap::new(),
+    };
+    let mut upper = Vec::with_capacity(mesh.indices.len());
+    let mut lower = Vec::with_capacity(mesh.indices.len());
+    let mut coplanar = vec![];
+    let mut segments = vec![];
+
+    for idx in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [idx[0], idx[1], idx[2]];
+        let [side_a, side_b, side_c] = [a, b, c].map(|idx| sides[idx as usize]);
+        // triangles on the plane are sorted in once it is known where the rest of the mesh lies
+        let mut push = |side: Side, triangle: [u32; 3]| match side {
+            Side::Above => upper.extend_from_slice(&triangle),
+            Side::Below => lower.extend_from_slice(&triangle),
+            Side::On => coplanar.push(triangle),
+        };
+
+        // rotate the triangle so that `a` is the vertex not sharing its side with the others
+        let ([a, b, c], [side_a, side_b, side_c]) = if side_b == side_c {
+            ([a, b, c], [side_a, side_b, side_c])
+        } else if side_a == side_c {
+            ([b, c, a], [side_b, side_c, side_a])
+        } else {
+            ([c, a, b], [side_c, side_a, side_b])
+        };
+
+        match (side_a, side_b, side_c) {
+            // the plane only touches the triangle, if at all
+            (_, Side::On, Side::On) => {
+                if side_a != Side::On {
+                    segments.push([b, c]);
+                }
+                push(side_a, [a, b, c]);
+            }
+            (Side::On, _, _) if side_b == side_c => push(side_b, [a, b, c]),
+            (_, _, _) if side_a == side_b => push(side_a, [a, b, c]),
+            // one point lies on the plane, the opposing edge crosses it
+            (Side::On, _, _) | (_, Side::On, _) | (_, _, Side::On) => {
+                let ([a, b, c], [side_b, side_c]) = if side_a == Side::On {
+                    ([a, b, c], [side_b, side_c])
+                } else if side_b == Side::On {
+                    ([b, c, a], [side_c, side_a])
+                } else {
+                    ([c, a, b], [side_a, side_b])
+                };
+                let ip = vertices.cut_edge(plane, ctor, b, c);
+                push(side_b, [a, b, ip]);
+                push(side_c, [a, ip, c]);
+                segments.push([ip, a]);
+            }
+            // `a` lies on one side, `b` and `c` on the other
+            _ => {
+                let ip_b = vertices.cut_edge(plane, ctor, a, b);
+                let ip_c = vertices.cut_edge(plane, ctor, a, c);
+                push(side_a, [a, ip_b, ip_c]);
+                push(side_b, [ip_b, b, c]);
+                push(side_b, [ip_b, c, ip_c]);
+                segments.push([ip_b, ip_c]);
+            }
+        }
+    }
+
+    let (above, below) = (!upper.is_empty(), !lower.is_empty());
+    for triangle in coplanar {
+        let corners = triangle.map(|idx| vertices.get(idx).pos());
+        match coplanar_side(plane, corners, above, below) {
+            Side::Below => lower.extend_from_slice(&triangle),
+            _ => upper.extend_from_slice(&triangle),
+        }
+    }
+
+    let pos = |idx: &[u32]| [0, 1, 2].map(|i| vertices.get(idx[i]).pos());
+    if tolerance.remove_degenerate {
+        upper = upper
+            .chunks_exact(3)
+            .filter(|idx| !is_degenerate(pos(idx), tolerance))
+            .flatten()
+            .copied()
+            .collect();
+        lower = lower
+            .chunks_exact(3)
+            .filter(|idx| !is_degenerate(pos(idx), tolerance))
+            .flatten()
+            .copied()
+            .collect();
+    }
+
+    if upper.is_empty() || lower.is_empty() {
+        // no slicing occured
+        let upper = upper.chunks_exact(3).map(pos);
+        return Ok(IndexedSliceResult::Unsliced(unsliced_outcome(
+            plane,
+            upper,
+            lower.is_empty(),
+            tolerance,
+        )));
+    }
+
+    let Cap {
+        points,
+        uvs,
+        tangent,
+        bitangent,
+        polygons,
+        ..
+    } = triangulate_loops(
+        segments,
+        |&idx| vertices.get(idx).pos(),
+        plane,
+        &texture_bounds,
+        tolerance,
+    );
+    let mut cap = |normal: [S; 3], flip: bool| IndexedMesh {
+        vertices: points
+            .iter()
+            .zip(&uvs)
+            .map(|(&idx, &uv)| {
+                let attributes = CapAttributes {
+                    uv,
+                    normal,
+                    tangent,
+                    bitangent,
+                };
+                ctor.cap_vertex(vertices.get(idx), plane, attributes)
+            })
+            .collect(),
+        indices: polygons
+            .iter()
+            .flatten()
+            .flat_map(|&[a, b, c]| if flip { [a, c, b] } else { [a, b, c] })
+            .map(|idx| idx as u32)
+            .collect(),
+    };
+    let upper_cross = cap(plane.normal(), false);
+    // reversed winding order and normal
+    let lower_cross = cap(negate_v3(plane.normal()), true);
+
+    Ok(IndexedSliceResult::Sliced(
+        IndexedSubMesh {
+            hull: compact(&vertices, upper),
+            cross_section: upper_cross,
+        },
+        IndexedSubMesh {
+            hull: compact(&vertices, lower),
+            cross_section: lower_cross,
+        },
+    ))
+}
+
+/// Builds a mesh containing only the vertices referenced by the indices.
+fn compact<S: Scalar, V: Vertex<S> + Clone>(
+    vertices: &CutVertices<'_, V>,
+    mut indices: Vec<u32>,
+) -> IndexedMesh<V> {
+    let mut remap = vec![u32::MAX; vertices.original.len() + vertices.cut.len()];
+    let mut compacted = vec![];
+    for idx in &mut indices {
+        let new = &mut remap[*idx as usize];
+        if *new == u32::MAX {
+            *new = compacted.len() as u32;
+            compacted.push(vertices.get(*idx).clone());
+        }
+        *idx = *new;
+    }
+    IndexedMesh::new(compacted, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::cube;
+    use crate::{
+        slice_indexed, IndexedMesh, Plane, SliceError, SliceOptions, SliceOutcome, TextureBounds,
+    };
+
+    #[test]
+    fn unsliced_outcome() {
+        let vertices = cube()
+            .into_iter()
+            .flat_map(|t| vec![t.a, t.b, t.c])
+            .collect::<Vec<_>>();
+        let indices = (0..vertices.len() as u32).collect();
+        let mesh = IndexedMesh::new(vertices, indices);
+        let outcome = |height: f32| {
+            let plane = Plane::from_pos_normal([0.0, 0.0, height], [0.0, 0.0, 1.0]);
+            slice_indexed(
+                &mesh,
+                plane,
+                TextureBounds::default(),
+                SliceOptions::default(),
+            )
+            .unwrap()
+            .outcome()
+        };
+        assert_eq!(outcome(0.0), SliceOutcome::Sliced);
+        assert_eq!(outcome(-1.0), SliceOutcome::AllAbove);
+        assert_eq!(outcome(1.0), SliceOutcome::AllBelow);
+        assert_eq!(outcome(2.0), SliceOutcome::AllBelow);
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let vertices = cube()
+            .into_iter()
+            .flat_map(|t| vec![t.a, t.b, t.c])
+            .collect::<Vec<_>>();
+        let slice = |indices: Vec<u32>| {
+            let mesh = IndexedMesh::new(vertices.clone(), indices);
+            let plane = Plane::from_pos_normal([0.0; 3], [0.0, 0.0, 1.0]);
+            slice_indexed(
+                &mesh,
+                plane,
+                TextureBounds::default(),
+                SliceOptions::default(),
+            )
+            .err()
+        };
+        assert_eq!(
+            slice(vec![0, 1, 2, 3]),
+            Some(SliceError::IncompleteTriangle { trailing: 1 })
+        );
+        let out_of_range = vertices.len() as u32;
+        assert_eq!(
+            slice(vec![0, 1, 2, 3, out_of_range, 5]),
+            Some(SliceError::IndexOutOfRange { triangle: 1 })
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Conversions and [`Vertex`](crate::Vertex) implementations for the types of common math libraries.
+//!
+//! Planes convert from and to 4 dimensional vectors holding the normal in `xyz` and the distance in
+//! `w`, the same layout [`Plane::new`](crate::Plane::new) takes. Positions implement `Vertex` directly, as do tuples
+//! of position, normal and texture coordinates in that order.
+
+#[cfg(feature = "glam")]
+mod glam_impls {
+    use crate::math::nlerp3;
+    use crate::{Plane, Vertex};
+    use ::glam::{DVec2, DVec3, DVec4, Vec2, Vec3, Vec3A, Vec4};
+
+    macro_rules! impl_glam {
+        ($s:ident, $vec2:ident, $vec3:ident, $vec4:ident) => {
+            impl From<Plane<$s>> for $vec4 {
+                #[inline]
+                fn from(plane: Plane<$s>) -> Self {
+                    $vec3::from(plane.normal()).extend(plane.dist())
+                }
+            }
+
+            impl From<$vec4> for Plane<$s> {
+                #[inline]
+                fn from(v: $vec4) -> Self {
+                    Plane::new(v.truncate().to_array(), v.w)
+                }
+            }
+
+            impl Vertex<$s> for $vec3 {
+                #[inline]
+                fn new_interpolated(a: &Self, b: &Self, t: $s) -> Self {
+                    a.lerp(*b, t)
+                }
+
+                #[inline]
+                fn new(pos: [$s; 3], _: [$s; 2], _: [$s; 3]) -> Self {
+                    $vec3::from(pos)
+                }
+
+                #[inline]
+                fn pos(&self) -> [$s; 3] {
+                    self.to_array()
+                }
+            }
+
+            impl Vertex<$s> for ($vec3, $vec3, $vec2) {
+                #[inline]
+                fn new_interpolated(a: &Self, b: &Self, t: $s) -> Self {
+                    (
+                        a.0.lerp(b.0, t),
+                        $vec3::from(nlerp3(a.1.to_array(), b.1.to_array(), t)),
+                        a.2.lerp(b.2, t),
+                    )
+                }
+
+                #[inline]
+                fn new(pos: [$s; 3], uv: [$s; 2], normal: [$s; 3]) -> Self {
+                    ($vec3::from(pos), $vec3::from(normal), $vec2::from(uv))
+                }
+
+                #[inline]
+                fn pos(&self) -> [$s; 3] {
+                    self.0.to_array()
+                }
+            }
+        };
+    }
+
+    impl_glam!(f32, Vec2, Vec3, Vec4);
+    impl_glam!(f64, DVec2, DVec3, DVec4);
+
+    impl Vertex for Vec3A {
+        #[inline]
+        fn new_interpolated(a: &Self, b: &Self, t: f32) -> Self {
+            a.lerp(*b, t)
+        }
+
+        #[inline]
+        fn new(pos: [f32; 3], _: [f32; 2], _: [f32; 3]) -> Self {
+            Vec3A::from(pos)
+        }
+
+        #[inline]
+        fn pos(&self) -> [f32; 3] {
+            self.to_array()
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+mod mint_impls {
+    use crate::math::{lerp3, nlerp3};
+    use crate::{Plane, Scalar, Vertex};
+    use ::mint::{Point3, Vector2, Vector3, Vector4};
+
+    impl<S: Scalar> From<Plane<S>> for Vector4<S> {
+        #[inline]
+        fn from(plane: Plane<S>) -> Self {
+            let [x, y, z] = plane.normal();
+            Vector4 {
+                x,
+                y,
+                z,
+                w: plane.dist(),
+            }
+        }
+    }
+
+    impl<S: Scalar> From<Vector4<S>> for Plane<S> {
+        #[inline]
+        fn from(v: Vector4<S>) -> Self {
+            Plane::new([v.x, v.y, v.z], v.w)
+        }
+    }
+
+    impl<S: Scalar> Vertex<S> for Point3<S> {
+        #[inline]
+        fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+            lerp3((*a).into(), (*b).into(), t).into()
+        }
+
+        #[inline]
+        fn new(pos: [S; 3], _: [S; 2], _: [S; 3]) -> Self {
+            pos.into()
+        }
+
+        #[inline]
+        fn pos(&self) -> [S; 3] {
+            (*self).into()
+        }
+    }
+
+    impl<S: Scalar> Vertex<S> for (Point3<S>, Vector3<S>, Vector2<S>) {
+        #[inline]
+        fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+            let [u, v] = [a.2.x + t * (b.2.x - a.2.x), a.2.y + t * (b.2.y - a.2.y)];
+            (
+                lerp3(a.0.into(), b.0.into(), t).into(),
+                nlerp3(a.1.into(), b.1.into(), t).into(),
+                Vector2 { x: u, y: v },
+            )
+        }
+
+        #[inline]
+        fn new(pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> Self {
+            (pos.into(), normal.into(), uv.into())
+        }
+
+        #[inline]
+        fn pos(&self) -> [S; 3] {
+            self.0.into()
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_impls {
+    use crate::math::{lerp3, nlerp3};
+    use crate::{Plane, Scalar, Vertex};
+    use ::nalgebra::{Point3, Vector2, Vector3, Vector4};
+
+    impl<S: Scalar + ::nalgebra::Scalar> From<Plane<S>> for Vector4<S> {
+        #[inline]
+        fn from(plane: Plane<S>) -> Self {
+            let [x, y, z] = plane.normal();
+            Vector4::new(x, y, z, plane.dist())
+        }
+    }
+
+    impl<S: Scalar + ::nalgebra::Scalar> From<Vector4<S>> for Plane<S> {
+        #[inline]
+        fn from(v: Vector4<S>) -> Self {
+            Plane::new([v.x, v.y, v.z], v.w)
+        }
+    }
+
+    impl<S: Scalar + ::nalgebra::Scalar> Vertex<S> for Point3<S> {
+        #[inline]
+        fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+            lerp3(a.pos(), b.pos(), t).into()
+        }
+
+        #[inline]
+        fn new(pos: [S; 3], _: [S; 2], _: [S; 3]) -> Self {
+            pos.into()
+        }
+
+        #[inline]
+        fn pos(&self) -> [S; 3] {
+            [self.x, self.y, self.z]
+        }
+    }
+
+    impl<S: Scalar + ::nalgebra::Scalar> Vertex<S> for (Point3<S>, Vector3<S>, Vector2<S>) {
+        #[inline]
+        fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+            let uv = [a.2.x + t * (b.2.x - a.2.x), a.2.y + t * (b.2.y - a.2.y)];
+            (
+                lerp3(a.pos(), b.pos(), t).into(),
+                nlerp3([a.1.x, a.1.y, a.1.z], [b.1.x, b.1.y, b.1.z], t).into(),
+                uv.into(),
+            )
+        }
+
+        #[inline]
+        fn new(pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> Self {
+            (pos.into(), normal.into(), uv.into())
+        }
+
+        #[inline]
+        fn pos(&self) -> [S; 3] {
+            [self.0.x, self.0.y, self.0.z]
+        }
+    }
+}
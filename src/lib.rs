@@ -1,20 +1,76 @@
+use std::borrow::Borrow;
+
+mod bounds;
+
+mod options;
+pub use self::options::SliceOptions;
+use self::options::Tolerance;
+
+mod error;
+pub use self::error::SliceError;
+
 mod triangle;
 use self::triangle::intersect_triangle;
 pub use self::triangle::Triangle;
 
 mod math;
-pub use self::math::{lerp2, lerp3};
+use self::math::{cross, dot_v3, magnitude, magnitude_squared, sub_v3};
+pub use self::math::{lerp2, lerp3, nlerp3};
 
 mod plane;
-pub use self::plane::Plane;
-use self::plane::Side;
+pub use self::plane::{Plane, Side};
+
+mod scalar;
+pub use self::scalar::Scalar;
+
+mod constructor;
+use self::constructor::NewVertex;
+pub use self::constructor::{CapAttributes, CapConstructor, VertexConstructor};
+
+mod projection;
+use self::projection::CapSpace;
+pub use self::projection::UvProjection;
+
+mod slicer;
+pub use self::slicer::{BorrowedSubMesh, SliceOutcome, Slicer};
 
 mod triangulate;
-use self::triangulate::triangulate;
+use self::triangulate::{
+    triangulate_caps, triangulate_segments, triangulate_side, try_triangulate_segments,
+};
+
+mod components;
+use self::components::split_components;
+
+mod indexed;
+pub use self::indexed::{
+    slice_indexed, slice_indexed_with, IndexedMesh, IndexedSliceResult, IndexedSubMesh,
+};
+
+mod multi;
+pub use self::multi::{slice_many, slice_many_with};
+
+mod fracture;
+pub use self::fracture::{
+    clustered_seeds, fracture_voronoi, fracture_voronoi_with, random_seeds, SeedRng,
+};
+
+mod vertices;
+pub use self::vertices::{PosNormalUvTangentVertex, PosNormalUvVertex, PosNormalVertex, PosVertex};
 
-const EPSILON: f32 = 1e-7;
+#[cfg(feature = "robust")]
+mod robust;
 
-pub fn vertex_to_triangle<'a, V: Vertex, I: IntoIterator<Item = V> + 'a>(
+#[cfg(feature = "parallel")]
+mod parallel;
+
+#[cfg(any(feature = "glam", feature = "mint", feature = "nalgebra"))]
+mod interop;
+
+#[cfg(test)]
+mod testing;
+
+pub fn vertex_to_triangle<'a, V, I: IntoIterator<Item = V> + 'a>(
     i: I,
 ) -> impl Iterator<Item = Triangle<V>> + 'a {
     let mut iter = i.into_iter();
@@ -26,7 +82,26 @@ pub fn vertex_to_triangle<'a, V: Vertex, I: IntoIterator<Item = V> + 'a>(
     })
 }
 
-pub fn triangle_to_vertex<'a, V: Vertex + 'a, I: IntoIterator<Item = Triangle<V>> + 'a>(
+/// Like [`vertex_to_triangle`], but reports an error for trailing vertices instead of panicking.
+pub fn try_vertex_to_triangle<'a, V, I: IntoIterator<Item = V> + 'a>(
+    i: I,
+) -> impl Iterator<Item = Result<Triangle<V>, SliceError>> + 'a {
+    let mut iter = i.into_iter();
+    std::iter::from_fn(move || {
+        let a = iter.next()?;
+        let b = match iter.next() {
+            Some(b) => b,
+            None => return Some(Err(SliceError::IncompleteTriangle { trailing: 1 })),
+        };
+        let c = match iter.next() {
+            Some(c) => c,
+            None => return Some(Err(SliceError::IncompleteTriangle { trailing: 2 })),
+        };
+        Some(Ok(Triangle { a, b, c }))
+    })
+}
+
+pub fn triangle_to_vertex<'a, V: 'a, I: IntoIterator<Item = Triangle<V>> + 'a>(
     i: I,
 ) -> impl Iterator<Item = V> + 'a {
     i.into_iter()
@@ -34,97 +109,730 @@ pub fn triangle_to_vertex<'a, V: Vertex + 'a, I: IntoIterator<Item = Triangle<V>
         .flat_map(|triangle| vec![triangle.a, triangle.b, triangle.c])
 }
 
+/// The region of the texture the cross section is mapped to, and how it is projected onto it.
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub struct TextureBounds {
-    x_min: f32,
-    y_min: f32,
-    x_max: f32,
-    y_max: f32,
+pub struct TextureBounds<S = f32> {
+    x_min: S,
+    y_min: S,
+    x_max: S,
+    y_max: S,
+    projection: UvProjection<S>,
+    tangent: Option<[S; 3]>,
 }
 
-impl TextureBounds {
+impl<S: Scalar> TextureBounds<S> {
     #[inline]
-    pub fn new(x_min: f32, y_min: f32, x_max: f32, y_max: f32) -> Self {
+    pub fn new(x_min: S, y_min: S, x_max: S, y_max: S) -> Self {
         TextureBounds {
             x_min,
             y_min,
             x_max,
             y_max,
+            projection: UvProjection::Stretch,
+            tangent: None,
         }
     }
 
-    /// returns a function that maps a vec2 in range [0;1] to the bounds of this texture
+    /// Sets how the cross section is projected onto the bounds, [`UvProjection::Stretch`] by default.
+    #[inline]
+    pub fn with_projection(mut self, projection: UvProjection<S>) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Orients the texture so that u grows along `tangent` projected onto the cutting plane, v then
+    /// grows along `cross(u, normal)`.
+    ///
+    /// Without a tangent, or if it is perpendicular to the plane, the orientation is derived from the
+    /// plane normal alone. This has no effect on [`UvProjection::Box`].
     #[inline]
-    fn mapper(&self) -> impl Fn([f32; 2]) -> [f32; 2] {
-        let diffx = self.x_max - self.x_min;
-        let diffy = self.y_max - self.y_min;
-        let x_min = self.x_min;
-        let y_min = self.y_min;
-        move |[x, y]| [x * diffx + x_min, y * diffy + y_min]
+    pub fn with_tangent(mut self, tangent: [S; 3]) -> Self {
+        self.tangent = Some(tangent);
+        self
     }
 }
 
-impl Default for TextureBounds {
+impl<S: Scalar> Default for TextureBounds<S> {
     #[inline]
     fn default() -> Self {
-        TextureBounds {
-            x_min: 0.0,
-            y_min: 0.0,
-            x_max: 1.0,
-            y_max: 1.0,
-        }
+        TextureBounds::new(S::ZERO, S::ZERO, S::ONE, S::ONE)
     }
 }
 
+#[derive(Clone)]
 pub struct SubMesh<V> {
     pub hull: Vec<Triangle<V>>,
     pub cross_section: Vec<Triangle<V>>,
 }
 
-pub fn slice_convex<V: Vertex + Clone>(
+impl<V> Default for SubMesh<V> {
+    #[inline]
+    fn default() -> Self {
+        SubMesh {
+            hull: Vec::new(),
+            cross_section: Vec::new(),
+        }
+    }
+}
+
+/// The outcome of slicing a mesh.
+///
+/// The halves are [`SubMesh`]es, except for [`slice_parts`] which hands back the pieces of each side.
+pub enum SliceResult<V, T = SubMesh<V>> {
+    /// The plane cut through the mesh, holding the upper and the lower half.
+    Sliced(T, T),
+    /// The mesh lies above the plane, possibly touching it, and is handed back untouched.
+    AllAbove(Vec<Triangle<V>>),
+    /// The mesh lies below the plane, possibly touching it, and is handed back untouched.
+    AllBelow(Vec<Triangle<V>>),
+    /// Every triangle of the mesh lies on the plane, the mesh is handed back untouched.
+    Coplanar(Vec<Triangle<V>>),
+    /// The mesh has no triangles.
+    Empty,
+}
+
+impl<V, T> SliceResult<V, T> {
+    /// Returns the upper and lower half if the mesh was sliced.
+    #[inline]
+    pub fn sliced(self) -> Option<(T, T)> {
+        match self {
+            SliceResult::Sliced(upper, lower) => Some((upper, lower)),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_sliced(&self) -> bool {
+        matches!(self, SliceResult::Sliced(..))
+    }
+}
+
+/// Classifies a mesh the plane didnt cut, at most one of the hulls may be non-empty.
+fn unsliced<S: Scalar, V: Vertex<S>, T>(
+    plane: Plane<S>,
+    upper: Vec<Triangle<V>>,
+    lower: Vec<Triangle<V>>,
+    tolerance: Tolerance<S>,
+) -> SliceResult<V, T> {
+    match unsliced_outcome(
+        plane,
+        upper.iter().map(corners),
+        lower.is_empty(),
+        tolerance,
+    ) {
+        SliceOutcome::AllBelow => SliceResult::AllBelow(lower),
+        SliceOutcome::Coplanar => SliceResult::Coplanar(upper),
+        SliceOutcome::AllAbove => SliceResult::AllAbove(upper),
+        SliceOutcome::Sliced | SliceOutcome::Empty => SliceResult::Empty,
+    }
+}
+
+/// Tells where the mesh lies if the plane did not cut through it, given the corners of the triangles
+/// sorted into the upper hull and whether any were sorted into the lower one.
+fn unsliced_outcome<S: Scalar>(
+    plane: Plane<S>,
+    upper: impl IntoIterator<Item = [[S; 3]; 3]>,
+    lower_is_empty: bool,
+    tolerance: Tolerance<S>,
+) -> SliceOutcome {
+    let mut upper = upper.into_iter().peekable();
+    if !lower_is_empty {
+        SliceOutcome::AllBelow
+    } else if upper.peek().is_none() {
+        SliceOutcome::Empty
+    } else if upper.all(|corners| {
+        // a mesh lying on the plane as a whole ends up in the upper hull
+        corners
+            .iter()
+            .all(|&pos| plane.classify_side_within(pos, tolerance) == Side::On)
+    }) {
+        SliceOutcome::Coplanar
+    } else {
+        SliceOutcome::AllAbove
+    }
+}
+
+struct Split<V> {
+    upper: Vec<Triangle<V>>,
+    lower: Vec<Triangle<V>>,
+    /// the triangles lying on the plane, sorted into the hulls once all others are
+    coplanar: Vec<Triangle<V>>,
+    /// the segments along which the plane cuts through the mesh
+    segments: Vec<[V; 2]>,
+    /// whether any part of the mesh was dropped for lying on the side not kept
+    discarded: bool,
+    /// whether any part of the mesh off the plane lies above it, kept or not
+    above: bool,
+    /// whether any part of the mesh off the plane lies below it, kept or not
+    below: bool,
+}
+
+impl<V> Split<V> {
+    fn new(upper: Vec<Triangle<V>>, lower: Vec<Triangle<V>>, segments: Vec<[V; 2]>) -> Self {
+        Split {
+            upper,
+            lower,
+            coplanar: Vec::new(),
+            segments,
+            discarded: false,
+            above: false,
+            below: false,
+        }
+    }
+
+    /// Returns the hull a triangle lying on the plane belongs to, or `None` if it is discarded, see
+    /// [`coplanar_side`].
+    fn coplanar_hull<S: Scalar>(
+        &mut self,
+        plane: Plane<S>,
+        corners: [[S; 3]; 3],
+        keep: Option<Keep>,
+    ) -> Option<Side> {
+        let side = coplanar_side(plane, corners, self.above, self.below);
+        self.keep(side, keep)
+    }
+
+    /// Appends the pieces split off another part of the mesh.
+    #[cfg(feature = "parallel")]
+    fn append(&mut self, other: Split<V>) {
+        self.upper.extend(other.upper);
+        self.lower.extend(other.lower);
+        self.coplanar.extend(other.coplanar);
+        self.segments.extend(other.segments);
+        self.discarded |= other.discarded;
+        self.above |= other.above;
+        self.below |= other.below;
+    }
+
+    /// Returns the side if it is kept, flagging that part of the mesh was discarded otherwise.
+    fn keep(&mut self, side: Side, keep: Option<Keep>) -> Option<Side> {
+        match (side, keep) {
+            (Side::Above, Some(Keep::Below)) | (Side::Below, Some(Keep::Above)) => {
+                self.discarded = true;
+                None
+            }
+            _ => Some(side),
+        }
+    }
+}
+
+/// Returns the side of the plane a triangle lying on it belongs to, given whether the rest of the mesh
+/// lies above and below the plane.
+///
+/// The triangle stays with the rest of the mesh if the mesh only touches the plane. If the plane cuts
+/// through the mesh, the triangle closes the part it faces away from. A mesh lying on the plane as a
+/// whole goes to the upper side.
+fn coplanar_side<S: Scalar>(
+    plane: Plane<S>,
+    [a, b, c]: [[S; 3]; 3],
+    above: bool,
+    below: bool,
+) -> Side {
+    match (above, below) {
+        (true, true) => {
+            if dot_v3(cross(sub_v3(b, a), sub_v3(c, a)), plane.normal()) > S::ZERO {
+                Side::Below
+            } else {
+                Side::Above
+            }
+        }
+        (false, true) => Side::Below,
+        _ => Side::Above,
+    }
+}
+
+/// Sorts the triangles into the upper and lower hull, splitting the ones that intersect the plane.
+///
+/// If only one side is to be kept, the triangles of the other side are dropped.
+fn split<S: Scalar, V: Vertex<S> + Clone>(
     triangles: impl IntoIterator<Item = Triangle<V>>,
-    plane: Plane,
-    texture_bounds: TextureBounds,
-) -> Option<(SubMesh<V>, SubMesh<V>)> {
+    plane: Plane<S>,
+    keep: Option<Keep>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Split<V> {
     let triangles = triangles.into_iter();
-    let mut upper = Vec::with_capacity(triangles.size_hint().0);
-    let mut lower = Vec::with_capacity(triangles.size_hint().0);
-    let mut cross = vec![];
-
-    for triangle in triangles {
-        if let Some((points, split)) = intersect_triangle(plane, triangle.clone()) {
-            split.append_to(&mut lower, &mut upper);
-            // FIXME: IntoIter for Arrays when
-            cross.extend(points.iter().cloned());
-        } else {
-            let side_a = plane.classify_side(triangle.a.pos());
-            let side_b = plane.classify_side(triangle.b.pos());
-            let side_c = plane.classify_side(triangle.c.pos());
-
-            // the plane didnt intersect this triangle, figure out into what hull to put it
-            let side = if side_a != Side::On {
-                side_a
-            } else if side_b != Side::On {
-                side_b
-            } else if side_c != Side::On {
-                side_c
-            } else {
-                Side::On
-            };
-            if let Side::Above | Side::On = side {
-                upper.push(triangle);
-            } else {
-                lower.push(triangle);
+    let mut split = Split::new(
+        Vec::with_capacity(triangles.size_hint().0),
+        Vec::with_capacity(triangles.size_hint().0),
+        vec![],
+    );
+    split_into(triangles, plane, keep, tolerance, ctor, &mut split);
+    split
+}
+
+/// Like [`split`], but appends to the lists of `split`.
+fn split_into<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    keep: Option<Keep>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+    split: &mut Split<V>,
+) {
+    split_triangles(triangles, plane, keep, tolerance, ctor, split);
+
+    let mut coplanar = std::mem::take(&mut split.coplanar);
+    for triangle in coplanar.drain(..) {
+        match split.coplanar_hull(plane, corners(&triangle), keep) {
+            Some(Side::Below) => split.lower.push(triangle),
+            Some(_) => split.upper.push(triangle),
+            None => (),
+        }
+    }
+    split.coplanar = coplanar;
+
+    let Split { upper, lower, .. } = split;
+    if tolerance.remove_degenerate {
+        remove_degenerate(upper, tolerance);
+        remove_degenerate(lower, tolerance);
+    }
+}
+
+/// Splits every triangle the plane cuts through, appending the pieces and the cut to `split`.
+///
+/// The triangles lying on the plane are left in [`Split::coplanar`].
+#[cfg(not(feature = "parallel"))]
+fn split_triangles<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    keep: Option<Keep>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+    split: &mut Split<V>,
+) {
+    split_serial(triangles, plane, keep, tolerance, ctor, split);
+}
+
+/// Splits every triangle the plane cuts through, appending the pieces and the cut to `split`.
+///
+/// Large meshes are split across threads if the constructor can be forked, see
+/// [`VertexConstructor::fork`]. The triangles lying on the plane are left in [`Split::coplanar`].
+#[cfg(feature = "parallel")]
+fn split_triangles<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    keep: Option<Keep>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+    split: &mut Split<V>,
+) {
+    let triangles = triangles.into_iter();
+    if !parallel::worth_spreading(triangles.size_hint().0) {
+        return split_serial(triangles, plane, keep, tolerance, ctor, split);
+    }
+    let triangles = triangles.collect::<Vec<_>>();
+    if let Err(triangles) = parallel::split(triangles, plane, keep, tolerance, ctor, split) {
+        split_serial(triangles, plane, keep, tolerance, ctor, split);
+    }
+}
+
+/// Splits the triangles one after another on the calling thread, see [`split_triangles`].
+fn split_serial<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    keep: Option<Keep>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+    split: &mut Split<V>,
+) {
+    for (triangle, sides) in classify_triangles(triangles, plane, tolerance) {
+        match split_triangle(&triangle, sides, plane, keep, tolerance, ctor, split) {
+            Some(Side::Above) => split.upper.push(triangle),
+            Some(Side::Below) => split.lower.push(triangle),
+            Some(Side::On) => split.coplanar.push(triangle),
+            None => (),
+        }
+    }
+}
+
+/// Splits the triangle if the plane cuts through it, appending the pieces and the cut to `split`.
+///
+/// Returns the hull an uncut triangle belongs to unless its side is discarded, or [`Side::On`] for a
+/// triangle lying on the plane, whose hull is only known once all others are split, see
+/// [`Split::coplanar_hull`].
+fn split_triangle<S: Scalar, V: Vertex<S> + Clone>(
+    triangle: &Triangle<V>,
+    [side_a, side_b, side_c]: [Side; 3],
+    plane: Plane<S>,
+    keep: Option<Keep>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+    split: &mut Split<V>,
+) -> Option<Side> {
+    // only triangles with points on both sides of the plane can be split
+    let sides = [side_a, side_b, side_c];
+    if sides.contains(&Side::Above) && sides.contains(&Side::Below) {
+        if let Some((points, cut)) =
+            intersect_triangle(plane, triangle.clone(), sides, tolerance, ctor)
+        {
+            match keep {
+                None => cut.append_to(&mut split.lower, &mut split.upper),
+                Some(Keep::Above) => cut.append_side_to(Side::Above, &mut split.upper),
+                Some(Keep::Below) => cut.append_side_to(Side::Below, &mut split.lower),
             }
+            split.discarded |= keep.is_some();
+            split.above = true;
+            split.below = true;
+            split.segments.push(points);
+            return None;
         }
     }
 
+    // an edge lying on the plane is part of the cut as well
+    let segments = &mut split.segments;
+    match (side_a, side_b, side_c) {
+        (Side::On, Side::On, Side::On) => (),
+        (Side::On, Side::On, _) => segments.push([triangle.a.clone(), triangle.b.clone()]),
+        (Side::On, _, Side::On) => segments.push([triangle.c.clone(), triangle.a.clone()]),
+        (_, Side::On, Side::On) => segments.push([triangle.b.clone(), triangle.c.clone()]),
+        _ => (),
+    }
+
+    // the plane didnt intersect this triangle, figure out into what hull to put it
+    let side = if side_a != Side::On {
+        side_a
+    } else if side_b != Side::On {
+        side_b
+    } else if side_c != Side::On {
+        side_c
+    } else {
+        Side::On
+    };
+    match side {
+        Side::On => return Some(Side::On),
+        Side::Above => split.above = true,
+        Side::Below => split.below = true,
+    }
+    split.keep(side, keep)
+}
+
+#[inline]
+fn corners<S: Scalar, V: Vertex<S>>(triangle: &Triangle<V>) -> [[S; 3]; 3] {
+    [triangle.a.pos(), triangle.b.pos(), triangle.c.pos()]
+}
+
+/// The sides of the plane the corners lie on.
+#[inline]
+fn classify_corners<S: Scalar>(
+    plane: Plane<S>,
+    [a, b, c]: [[S; 3]; 3],
+    tolerance: Tolerance<S>,
+) -> [Side; 3] {
+    [
+        plane.classify_side_within(a, tolerance),
+        plane.classify_side_within(b, tolerance),
+        plane.classify_side_within(c, tolerance),
+    ]
+}
+
+/// Pairs every triangle with the sides of the plane its corners lie on.
+#[cfg(not(feature = "parallel"))]
+fn classify_triangles<S: Scalar, V: Vertex<S>, T: Borrow<Triangle<V>>>(
+    triangles: impl IntoIterator<Item = T>,
+    plane: Plane<S>,
+    tolerance: Tolerance<S>,
+) -> impl Iterator<Item = (T, [Side; 3])> {
+    triangles.into_iter().map(move |triangle| {
+        let sides = classify_corners(plane, corners(triangle.borrow()), tolerance);
+        (triangle, sides)
+    })
+}
+
+/// Pairs every triangle with the sides of the plane its corners lie on, classifying them across
+/// threads if there are enough of them.
+///
+/// The triangles have to be gathered first, which only pays off for large meshes, so triangles whose
+/// iterator doesnt promise that many are classified on the calling thread instead.
+#[cfg(feature = "parallel")]
+fn classify_triangles<S: Scalar, V: Vertex<S>, T: Borrow<Triangle<V>>>(
+    triangles: impl IntoIterator<Item = T>,
+    plane: Plane<S>,
+    tolerance: Tolerance<S>,
+) -> impl Iterator<Item = (T, [Side; 3])> {
+    let triangles = triangles.into_iter();
+    let (serial, spread) = if parallel::worth_spreading(triangles.size_hint().0) {
+        let triangles = triangles.collect::<Vec<_>>();
+        let sides = parallel::classify(&triangles, plane, tolerance);
+        (None, Some(triangles.into_iter().zip(sides)))
+    } else {
+        (Some(triangles), None)
+    };
+    let serial = serial.into_iter().flatten().map(move |triangle| {
+        let sides = classify_corners(plane, corners(triangle.borrow()), tolerance);
+        (triangle, sides)
+    });
+    serial.chain(spread.into_iter().flatten())
+}
+
+/// Removes the triangles that are degenerate within the tolerance, keeping the order of the rest.
+#[cfg(not(feature = "parallel"))]
+fn remove_degenerate<S: Scalar, V: Vertex<S>>(
+    triangles: &mut Vec<Triangle<V>>,
+    tolerance: Tolerance<S>,
+) {
+    triangles.retain(|t| !is_degenerate(corners(t), tolerance));
+}
+
+/// Removes the triangles that are degenerate within the tolerance, keeping the order of the rest
+/// and testing them across threads.
+#[cfg(feature = "parallel")]
+fn remove_degenerate<S: Scalar, V: Vertex<S>>(
+    triangles: &mut Vec<Triangle<V>>,
+    tolerance: Tolerance<S>,
+) {
+    if !parallel::worth_spreading(triangles.len()) {
+        triangles.retain(|t| !is_degenerate(corners(t), tolerance));
+        return;
+    }
+    let mut degenerate = parallel::degenerate(triangles, tolerance).into_iter();
+    // retain visits the triangles in order
+    triangles.retain(|_| !degenerate.next().unwrap_or(false));
+}
+
+/// Whether the triangle is too thin to matter, that is its height over the longest edge lies within
+/// the tolerance.
+fn is_degenerate<S: Scalar>([a, b, c]: [[S; 3]; 3], tolerance: Tolerance<S>) -> bool {
+    let double_area = magnitude(cross(sub_v3(b, a), sub_v3(c, a)));
+    let longest = magnitude_squared(sub_v3(b, a))
+        .max(magnitude_squared(sub_v3(c, b)))
+        .max(magnitude_squared(sub_v3(a, c)))
+        .sqrt();
+    double_area <= tolerance.eps * longest
+}
+
+pub fn slice_convex<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> SliceResult<V> {
+    slice_convex_with(triangles, plane, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`slice_convex`], but creates the new vertices with the given constructor.
+pub fn slice_convex_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> SliceResult<V> {
+    slice_convex_in(triangles, plane, texture_bounds, options, None, ctor)
+}
+
+/// Like [`slice_convex`], but borrows the triangles and only clones the ones the plane cuts through.
+///
+/// The triangles the plane does not cut come back as indices into `triangles`, see
+/// [`BorrowedSubMesh`]. To slice many meshes, [`Slicer::slice_borrowed_into`] reuses its buffers.
+pub fn slice_convex_borrowed<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: &[Triangle<V>],
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> (SliceOutcome, BorrowedSubMesh<V>, BorrowedSubMesh<V>) {
+    slice_convex_borrowed_with(triangles, plane, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`slice_convex_borrowed`], but creates the new vertices with the given constructor.
+pub fn slice_convex_borrowed_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: &[Triangle<V>],
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> (SliceOutcome, BorrowedSubMesh<V>, BorrowedSubMesh<V>) {
+    let (mut upper, mut lower) = (BorrowedSubMesh::default(), BorrowedSubMesh::default());
+    let outcome = Slicer::new().slice_borrowed_into_with(
+        triangles,
+        plane,
+        texture_bounds,
+        options,
+        &mut upper,
+        &mut lower,
+        ctor,
+    );
+    (outcome, upper, lower)
+}
+
+/// Like [`slice_convex`], but for a mesh in object space cut by a plane in world space.
+///
+/// The mesh is cut in object space without transforming its vertices, `object_to_world` is the
+/// affine transform of the mesh given as columns, as returned by `to_cols_array_2d` in glam or held
+/// by a mint `ColumnMatrix4`. It may scale non-uniformly or mirror the mesh, but fails with
+/// [`SliceError::SingularTransform`] if it isnt invertible. The caps come out in object space, with
+/// normals that face along `world_plane` once transformed like the normals of the mesh, and with
+/// texture coordinates and tangents projected in world space, so textures are not distorted by the
+/// scale of the mesh.
+pub fn slice_convex_transformed<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    object_to_world: [[S; 4]; 4],
+    world_plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> Result<SliceResult<V>, SliceError> {
+    slice_convex_transformed_with(
+        triangles,
+        object_to_world,
+        world_plane,
+        texture_bounds,
+        options,
+        &mut NewVertex,
+    )
+}
+
+/// Like [`slice_convex_transformed`], but creates the new vertices with the given constructor.
+///
+/// The constructor is handed the cutting plane in object space.
+pub fn slice_convex_transformed_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    object_to_world: [[S; 4]; 4],
+    world_plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Result<SliceResult<V>, SliceError> {
+    let space = CapSpace::new(object_to_world, world_plane).ok_or(SliceError::SingularTransform)?;
+    let plane = space.local_plane();
+    Ok(slice_convex_in(
+        triangles,
+        plane,
+        texture_bounds,
+        options,
+        Some(&space),
+        ctor,
+    ))
+}
+
+fn slice_convex_in<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    space: Option<&CapSpace<S>>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> SliceResult<V> {
+    let triangles = triangles.into_iter().collect::<Vec<_>>();
+    let tolerance = options.resolve(&triangles);
+    let mut upper = SubMesh::default();
+    let mut lower = SubMesh::default();
+    let outcome = Slicer::new().slice_in(
+        triangles,
+        plane,
+        &texture_bounds,
+        tolerance,
+        space,
+        [&mut upper, &mut lower],
+        ctor,
+    );
+    match outcome {
+        SliceOutcome::Sliced => SliceResult::Sliced(upper, lower),
+        SliceOutcome::AllAbove => SliceResult::AllAbove(upper.hull),
+        SliceOutcome::AllBelow => SliceResult::AllBelow(lower.hull),
+        SliceOutcome::Coplanar => SliceResult::Coplanar(upper.hull),
+        SliceOutcome::Empty => SliceResult::Empty,
+    }
+}
+
+/// Slices an arbitrary, possibly concave, closed mesh.
+///
+/// Unlike [`slice_convex`] the cut segments are chained into closed loops which are then triangulated
+/// one by one, so the cross section follows the actual outline of the cut instead of its convex hull.
+pub fn slice<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> SliceResult<V> {
+    slice_with(triangles, plane, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`slice()`], but creates the new vertices with the given constructor.
+pub fn slice_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> SliceResult<V> {
+    let triangles = triangles.into_iter().collect::<Vec<_>>();
+    let tolerance = options.resolve(&triangles);
+    let Split {
+        upper,
+        lower,
+        segments,
+        ..
+    } = split(triangles, plane, None, tolerance, ctor);
+
+    if !(upper.is_empty() || lower.is_empty()) {
+        let (lower_cross, upper_cross) =
+            triangulate_segments(segments, plane, &texture_bounds, tolerance, ctor)
+                .unwrap_or_default();
+
+        SliceResult::Sliced(
+            SubMesh {
+                hull: upper,
+                cross_section: upper_cross,
+            },
+            SubMesh {
+                hull: lower,
+                cross_section: lower_cross,
+            },
+        )
+    } else {
+        // no slicing occured
+        unsliced(plane, upper, lower, tolerance)
+    }
+}
+
+/// Like [`slice()`], but validates the input instead of producing garbage for it.
+///
+/// Fails if the plane or any vertex position isnt finite, if the plane normal has zero length or if
+/// the cross section could not be triangulated, which usually means the mesh isnt closed.
+pub fn try_slice<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> Result<SliceResult<V>, SliceError> {
+    try_slice_with(triangles, plane, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`try_slice`], but creates the new vertices with the given constructor.
+pub fn try_slice_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Result<SliceResult<V>, SliceError> {
+    plane.validate()?;
+    let triangles = triangles
+        .into_iter()
+        .enumerate()
+        .map(|(idx, triangle)| {
+            let positions = [triangle.a.pos(), triangle.b.pos(), triangle.c.pos()];
+            if positions.iter().flatten().all(|x| x.is_finite()) {
+                Ok(triangle)
+            } else {
+                Err(SliceError::NonFinitePosition { triangle: idx })
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let tolerance = options.resolve(&triangles);
+
+    let Split {
+        upper,
+        lower,
+        segments,
+        ..
+    } = split(triangles, plane, None, tolerance, ctor);
+
     if !(upper.is_empty() || lower.is_empty()) {
-        let (lower_cross, upper_cross) = triangulate(cross, plane, &texture_bounds)
-            // only happens if we didnt gather enough vertices to form a triangle
-            .unwrap_or_default();
+        let (lower_cross, upper_cross) =
+            try_triangulate_segments(segments, plane, &texture_bounds, tolerance, ctor)?;
 
-        Some((
+        Ok(SliceResult::Sliced(
             SubMesh {
                 hull: upper,
                 cross_section: upper_cross,
@@ -136,38 +844,167 @@ pub fn slice_convex<V: Vertex + Clone>(
         ))
     } else {
         // no slicing occured
-        None
+        Ok(unsliced(plane, upper, lower, tolerance))
+    }
+}
+
+/// Slices an arbitrary, possibly concave, closed mesh like [`slice()`], but splits each side into its
+/// disconnected pieces.
+///
+/// Each piece gets the parts of the cross section that close it, so every returned [`SubMesh`] is a
+/// closed mesh on its own. A mesh the plane doesnt cut is handed back like [`slice()`] does.
+pub fn slice_parts<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> SliceResult<V, Vec<SubMesh<V>>> {
+    slice_parts_with(triangles, plane, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`slice_parts`], but creates the new vertices with the given constructor.
+pub fn slice_parts_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> SliceResult<V, Vec<SubMesh<V>>> {
+    let triangles = triangles.into_iter().collect::<Vec<_>>();
+    let tolerance = options.resolve(&triangles);
+    let Split {
+        upper,
+        lower,
+        segments,
+        ..
+    } = split(triangles, plane, None, tolerance, ctor);
+
+    if !(upper.is_empty() || lower.is_empty()) {
+        let (lower_caps, upper_caps) =
+            triangulate_caps(segments, plane, &texture_bounds, tolerance, ctor)
+                .into_iter()
+                .unzip();
+
+        SliceResult::Sliced(
+            split_components(upper, upper_caps),
+            split_components(lower, lower_caps),
+        )
+    } else {
+        // no slicing occured
+        unsliced(plane, upper, lower, tolerance)
     }
 }
 
+/// The side of the plane to keep when clipping.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Keep {
+    /// Keep the part on the side the plane normal points to.
+    Above,
+    /// Keep the part on the side opposing the plane normal.
+    Below,
+}
+
+/// Clips an arbitrary, possibly concave, closed mesh at the plane, only keeping one side.
+///
+/// This only builds the hull and cross section of the kept side. Returns `None` if nothing of the mesh
+/// lies on that side, which includes a mesh only touching the plane from the other side and a mesh
+/// lying on the plane.
+pub fn clip<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    keep: Keep,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> Option<SubMesh<V>> {
+    clip_with(
+        triangles,
+        plane,
+        keep,
+        texture_bounds,
+        options,
+        &mut NewVertex,
+    )
+}
+
+/// Like [`clip`], but creates the new vertices with the given constructor.
+pub fn clip_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    plane: Plane<S>,
+    keep: Keep,
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Option<SubMesh<V>> {
+    let triangles = triangles.into_iter().collect::<Vec<_>>();
+    let tolerance = options.resolve(&triangles);
+    let Split {
+        upper,
+        lower,
+        segments,
+        discarded,
+        above,
+        below,
+        ..
+    } = split(triangles, plane, Some(keep), tolerance, ctor);
+    let (hull, side, solid) = match keep {
+        Keep::Above => (upper, Side::Above, above),
+        Keep::Below => (lower, Side::Below, below),
+    };
+    // faces on the plane alone dont enclose anything
+    if hull.is_empty() || !solid {
+        return None;
+    }
+
+    // the mesh only touches the plane if nothing was cut away, so there is no hole to close
+    let cross_section = if discarded {
+        triangulate_side(segments, plane, &texture_bounds, tolerance, side, ctor)
+    } else {
+        vec![]
+    };
+    Some(SubMesh {
+        hull,
+        cross_section,
+    })
+}
+
 /// Trait to be implemented by vertices for slicing
-pub trait Vertex: Sized {
+///
+/// The scalar type `S` is the type the position is made of, it defaults to `f32`.
+///
+/// With the `parallel` feature, vertices have to be [`Send`] as well, see [`MaybeSend`].
+pub trait Vertex<S: Scalar = f32>: Sized + MaybeSend {
     /// Creates a new vertex that will lie between the two given ones where t is a value between 0.0 and 1.0.
     /// This will be called to create the vertices that lie on the cutting plane where the mesh intersects with the plane.
-    fn new_interpolated(a: &Self, b: &Self, t: f32) -> Self;
+    fn new_interpolated(a: &Self, b: &Self, t: S) -> Self;
 
     /// Create a new vertex from the given position, uv and normal.
     /// This is solely used when constructing the cross section and will therefor be called twice per vertex
     /// with opposing normals.
-    fn new(pos: [f32; 3], uv: [f32; 2], normal: [f32; 3]) -> Self;
+    fn new(pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> Self;
+
+    /// Create a new vertex of the cross section from the given position and cap attributes.
+    /// Override this to make use of the tangent frame of the cap, by default this calls [`Vertex::new`].
+    #[inline]
+    fn new_cap(pos: [S; 3], attributes: CapAttributes<S>) -> Self {
+        Self::new(pos, attributes.uv, attributes.normal)
+    }
 
     /// Retrieves the position of this vertex.
-    fn pos(&self) -> [f32; 3];
+    fn pos(&self) -> [S; 3];
 }
 
-/*
-pub trait VertexConstructor<V> {
-    fn vertex(&mut self, pos: [f32; 3], uv: [f32; 2], normal: [f32; 3]) -> V;
-    fn interpolated(&mut self, a: &V, b: &V, t: f32) -> V;
-}
+/// [`Send`] with the `parallel` feature, which splits the triangles across threads, and implemented
+/// for every type otherwise.
+#[cfg(feature = "parallel")]
+pub trait MaybeSend: Send {}
 
-impl<V, VB: VertexConstructor<V>> VertexConstructor<V> for &mut VB {
-    fn vertex(&mut self, pos: [f32; 3], uv: [f32; 2], normal: [f32; 3]) -> V {
-        VB::vertex(self, pos, uv, normal)
-    }
+#[cfg(feature = "parallel")]
+impl<T: Send> MaybeSend for T {}
 
-    fn interpolated(&mut self, a: &V, b: &V, t: f32) -> V {
-        VB::interpolated(self, a, b, t)
-    }
-}
-*/
+/// [`Send`] with the `parallel` feature, which splits the triangles across threads, and implemented
+/// for every type otherwise.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> MaybeSend for T {}
@@ -1,30 +1,43 @@
+use crate::Scalar;
+
+/// Bitwise key of a position, usable for hashing.
 #[inline]
-pub fn sub_v2([lhs_x, lhs_y]: [f32; 2], [rhs_x, rhs_y]: [f32; 2]) -> [f32; 2] {
-    [lhs_x - rhs_x, lhs_y - rhs_y]
+pub fn pos_key<S: Scalar>([x, y, z]: [S; 3]) -> [u64; 3] {
+    // adding zero turns negative zero into positive zero so both map to the same key
+    [
+        (x + S::ZERO).to_bits(),
+        (y + S::ZERO).to_bits(),
+        (z + S::ZERO).to_bits(),
+    ]
 }
 
 #[inline]
-pub fn div_v2([lhs_x, lhs_y]: [f32; 2], [rhs_x, rhs_y]: [f32; 2]) -> [f32; 2] {
-    [lhs_x / rhs_x, lhs_y / rhs_y]
+pub fn add_v3<S: Scalar>([lhs_x, lhs_y, lhs_z]: [S; 3], [rhs_x, rhs_y, rhs_z]: [S; 3]) -> [S; 3] {
+    [lhs_x + rhs_x, lhs_y + rhs_y, lhs_z + rhs_z]
 }
 
 #[inline]
-pub fn sub_v3([lhs_x, lhs_y, lhs_z]: [f32; 3], [rhs_x, rhs_y, rhs_z]: [f32; 3]) -> [f32; 3] {
+pub fn sub_v3<S: Scalar>([lhs_x, lhs_y, lhs_z]: [S; 3], [rhs_x, rhs_y, rhs_z]: [S; 3]) -> [S; 3] {
     [lhs_x - rhs_x, lhs_y - rhs_y, lhs_z - rhs_z]
 }
 
 #[inline]
-pub fn negate_v3([x, y, z]: [f32; 3]) -> [f32; 3] {
+pub fn negate_v3<S: Scalar>([x, y, z]: [S; 3]) -> [S; 3] {
     [-x, -y, -z]
 }
 
 #[inline]
-pub fn dot_v3([lhs_x, lhs_y, lhs_z]: [f32; 3], [rhs_x, rhs_y, rhs_z]: [f32; 3]) -> f32 {
+pub fn scale_v3<S: Scalar>([x, y, z]: [S; 3], s: S) -> [S; 3] {
+    [x * s, y * s, z * s]
+}
+
+#[inline]
+pub fn dot_v3<S: Scalar>([lhs_x, lhs_y, lhs_z]: [S; 3], [rhs_x, rhs_y, rhs_z]: [S; 3]) -> S {
     lhs_x * rhs_x + lhs_y * rhs_y + lhs_z * rhs_z
 }
 
 #[inline]
-pub fn cross([lhs_x, lhs_y, lhs_z]: [f32; 3], [rhs_x, rhs_y, rhs_z]: [f32; 3]) -> [f32; 3] {
+pub fn cross<S: Scalar>([lhs_x, lhs_y, lhs_z]: [S; 3], [rhs_x, rhs_y, rhs_z]: [S; 3]) -> [S; 3] {
     [
         lhs_y.mul_add(rhs_z, -lhs_z * rhs_y),
         lhs_z.mul_add(rhs_x, -lhs_x * rhs_z),
@@ -33,29 +46,40 @@ pub fn cross([lhs_x, lhs_y, lhs_z]: [f32; 3], [rhs_x, rhs_y, rhs_z]: [f32; 3]) -
 }
 
 #[inline]
-pub fn magnitude_squared([x, y, z]: [f32; 3]) -> f32 {
+pub fn magnitude_squared<S: Scalar>([x, y, z]: [S; 3]) -> S {
     x.mul_add(x, y.mul_add(y, z * z))
 }
 
 #[inline]
-pub fn magnitude(v: [f32; 3]) -> f32 {
+pub fn magnitude<S: Scalar>(v: [S; 3]) -> S {
     magnitude_squared(v).sqrt()
 }
 
 #[inline]
-pub fn normalized([x, y, z]: [f32; 3]) -> [f32; 3] {
+pub fn normalized<S: Scalar>([x, y, z]: [S; 3]) -> [S; 3] {
     let mag = magnitude([x, y, z]);
     [x / mag, y / mag, z / mag]
 }
 
 /// Inprecise liner interpolation.
 #[inline]
-pub fn lerp3([x, y, z]: [f32; 3], [x2, y2, z2]: [f32; 3], t: f32) -> [f32; 3] {
+pub fn lerp3<S: Scalar>([x, y, z]: [S; 3], [x2, y2, z2]: [S; 3], t: S) -> [S; 3] {
     [x + t * (x2 - x), y + t * (y2 - y), z + t * (z2 - z)]
 }
 
 /// Inprecise liner interpolation.
 #[inline]
-pub fn lerp2([x, y, z]: [f32; 3], [x2, y2, z2]: [f32; 3], t: f32) -> [f32; 3] {
+pub fn lerp2<S: Scalar>([x, y, z]: [S; 3], [x2, y2, z2]: [S; 3], t: S) -> [S; 3] {
     [x + t * (x2 - x), y + t * (y2 - y), z + t * (z2 - z)]
 }
+
+/// Interpolates between two directions, renormalizing the result unless it vanishes.
+#[inline]
+pub fn nlerp3<S: Scalar>(a: [S; 3], b: [S; 3], t: S) -> [S; 3] {
+    let v = lerp3(a, b, t);
+    if magnitude_squared(v).is_normal() {
+        normalized(v)
+    } else {
+        v
+    }
+}
@@ -0,0 +1,111 @@
+use crate::bounds::Aabb;
+use crate::constructor::NewVertex;
+use crate::options::Tolerance;
+use crate::triangulate::triangulate_segments;
+use crate::{
+    split, Plane, Scalar, SliceOptions, Split, SubMesh, TextureBounds, Triangle, Vertex,
+    VertexConstructor,
+};
+
+/// Cuts a cell in two, returning it untouched if the plane doesnt pass through it.
+#[allow(clippy::type_complexity)]
+pub(crate) fn slice_cell<S: Scalar, V: Vertex<S> + Clone>(
+    cell: SubMesh<V>,
+    plane: Plane<S>,
+    texture_bounds: &TextureBounds<S>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Result<(SubMesh<V>, SubMesh<V>), SubMesh<V>> {
+    let hull = split(cell.hull, plane, None, tolerance, ctor);
+    let caps = split(cell.cross_section, plane, None, tolerance, ctor);
+
+    let upper_empty = hull.upper.is_empty() && caps.upper.is_empty();
+    let lower_empty = hull.lower.is_empty() && caps.lower.is_empty();
+    if upper_empty || lower_empty {
+        // nothing was split, so one side holds the cell as it was
+        let take = |Split { upper, lower, .. }: Split<V>| -> Vec<Triangle<V>> {
+            if upper_empty {
+                lower
+            } else {
+                upper
+            }
+        };
+        return Err(SubMesh {
+            hull: take(hull),
+            cross_section: take(caps),
+        });
+    }
+
+    // the caps of previous cuts are part of the new cut's outline
+    let mut segments = hull.segments;
+    segments.extend(caps.segments);
+    let (lower_cross, upper_cross) =
+        triangulate_segments(segments, plane, texture_bounds, tolerance, ctor).unwrap_or_default();
+
+    let mut upper_caps = caps.upper;
+    upper_caps.extend(upper_cross);
+    let mut lower_caps = caps.lower;
+    lower_caps.extend(lower_cross);
+    Ok((
+        SubMesh {
+            hull: hull.upper,
+            cross_section: upper_caps,
+        },
+        SubMesh {
+            hull: hull.lower,
+            cross_section: lower_caps,
+        },
+    ))
+}
+
+/// Slices the mesh with all of the given planes, returning every resulting cell with its caps.
+///
+/// The mesh may be concave. Cells are only cut by the planes passing through their bounding box.
+pub fn slice_many<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    planes: &[Plane<S>],
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+) -> Vec<SubMesh<V>> {
+    slice_many_with(triangles, planes, texture_bounds, options, &mut NewVertex)
+}
+
+/// Like [`slice_many`], but creates the new vertices with the given constructor.
+pub fn slice_many_with<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: impl IntoIterator<Item = Triangle<V>>,
+    planes: &[Plane<S>],
+    texture_bounds: TextureBounds<S>,
+    options: SliceOptions<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Vec<SubMesh<V>> {
+    let mesh = SubMesh {
+        hull: triangles.into_iter().collect(),
+        cross_section: vec![],
+    };
+    if mesh.hull.is_empty() {
+        return vec![];
+    }
+    let aabb = Aabb::of(&mesh);
+    let tolerance = options.resolve_extent(aabb.diagonal());
+    let mut cells = vec![(aabb, mesh)];
+
+    for &plane in planes {
+        let mut next = Vec::with_capacity(cells.len() * 2);
+        for (aabb, cell) in cells {
+            if !aabb.intersects(plane, tolerance) {
+                next.push((aabb, cell));
+                continue;
+            }
+            match slice_cell(cell, plane, &texture_bounds, tolerance, ctor) {
+                Ok((upper, lower)) => {
+                    next.push((Aabb::of(&upper), upper));
+                    next.push((Aabb::of(&lower), lower));
+                }
+                Err(cell) => next.push((aabb, cell)),
+            }
+        }
+        cells = next;
+    }
+
+    cells.into_iter().map(|(_, cell)| cell).collect()
+}
@@ -0,0 +1,100 @@
+use crate::bounds::Aabb;
+use crate::{Scalar, Triangle, Vertex};
+
+/// Controls the numerical tolerances used while slicing.
+///
+/// The tolerance actually used is the larger of the absolute one and the relative one scaled by the
+/// diagonal of the mesh's bounding box, so the default works for tiny and huge meshes alike.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SliceOptions<S = f32> {
+    /// Distance to the plane within which points count as lying on it, regardless of the mesh's size.
+    ///
+    /// Raising this is the way to treat vertices close to the plane as lying on it, avoiding the
+    /// sliver triangles a cut right next to them would produce. Such vertices are not moved, so the
+    /// cut and the caps may lie up to this distance off the plane.
+    pub absolute_tolerance: S,
+    /// Distance to the plane within which points count as lying on it, relative to the diagonal of
+    /// the mesh's bounding box.
+    pub relative_tolerance: S,
+    /// Whether points within the tolerance count as lying on the plane.
+    ///
+    /// If disabled, only points exactly on the plane count as lying on it and the tolerance is only
+    /// used to accept cuts slightly outside of an edge.
+    pub snap_to_plane: bool,
+    /// Whether triangles of (nearly) zero area are removed from the hulls after slicing.
+    pub remove_degenerate: bool,
+}
+
+impl<S: Scalar> SliceOptions<S> {
+    #[inline]
+    pub fn new(absolute_tolerance: S, relative_tolerance: S, snap_to_plane: bool) -> Self {
+        SliceOptions {
+            absolute_tolerance,
+            relative_tolerance,
+            snap_to_plane,
+            ..SliceOptions::default()
+        }
+    }
+
+    /// The tolerance to use for a mesh whose bounding box has the given diagonal.
+    #[inline]
+    pub fn tolerance(&self, extent: S) -> S {
+        self.absolute_tolerance
+            .max(self.relative_tolerance * extent)
+    }
+
+    pub(crate) fn resolve<V: Vertex<S>>(&self, triangles: &[Triangle<V>]) -> Tolerance<S> {
+        self.resolve_extent(Aabb::of_triangles(triangles).diagonal())
+    }
+
+    pub(crate) fn resolve_extent(&self, extent: S) -> Tolerance<S> {
+        Tolerance {
+            eps: self.tolerance(extent),
+            snap: self.snap_to_plane,
+            remove_degenerate: self.remove_degenerate,
+        }
+    }
+}
+
+impl<S: Scalar> Default for SliceOptions<S> {
+    #[inline]
+    fn default() -> Self {
+        SliceOptions {
+            absolute_tolerance: S::DEFAULT_TOLERANCE,
+            relative_tolerance: S::from_f64(1e-6),
+            snap_to_plane: true,
+            remove_degenerate: false,
+        }
+    }
+}
+
+/// The tolerance resolved for a specific mesh.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Tolerance<S> {
+    pub eps: S,
+    pub snap: bool,
+    pub remove_degenerate: bool,
+}
+
+impl<S: Scalar> Tolerance<S> {
+    /// Distance to the plane within which points count as lying on it.
+    #[inline]
+    pub(crate) fn on_plane(&self) -> S {
+        if self.snap {
+            self.eps
+        } else {
+            S::ZERO
+        }
+    }
+}
+
+impl<S: Scalar> Default for Tolerance<S> {
+    #[inline]
+    fn default() -> Self {
+        Tolerance {
+            eps: S::DEFAULT_TOLERANCE,
+            snap: true,
+            remove_degenerate: false,
+        }
+    }
+}
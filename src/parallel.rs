@@ -0,0 +1,141 @@
+//! Spreads the per triangle work of the slicer across threads for the `parallel` feature.
+//!
+//! Only positions are handed to the threads. The vertices and the vertex constructor stay on the
+//! calling thread and see the triangles in their original order, so the output is the same as
+//! without the feature.
+//!
+//! Splitting the triangles creates vertices though, so large meshes are split in chunks, each with
+//! its own constructor forked off the given one, see [`VertexConstructor::fork`]. The pieces of the
+//! chunks are put back together in order, so the output is still the same. Constructors that cannot
+//! be forked keep splitting on the calling thread.
+
+use std::borrow::Borrow;
+
+use rayon::prelude::*;
+
+use crate::options::Tolerance;
+use crate::{
+    classify_corners, corners, is_degenerate, split_serial, Keep, Plane, Scalar, Side, Split,
+    Triangle, Vertex, VertexConstructor,
+};
+
+/// Minimum number of triangles per task, so small meshes are not split into tiny tasks.
+const MIN_TASK_LEN: usize = 1024;
+
+/// Whether spreading the work over the triangles is worth gathering their positions first.
+pub(crate) fn worth_spreading(len: usize) -> bool {
+    len >= 2 * MIN_TASK_LEN && rayon::current_num_threads() > 1
+}
+
+/// Classifies the corners of every triangle against the plane.
+pub(crate) fn classify<S: Scalar, V: Vertex<S>, T: Borrow<Triangle<V>>>(
+    triangles: &[T],
+    plane: Plane<S>,
+    tolerance: Tolerance<S>,
+) -> Vec<[Side; 3]> {
+    let corners = triangles
+        .iter()
+        .map(|t| corners(t.borrow()))
+        .collect::<Vec<_>>();
+    corners
+        .into_par_iter()
+        .with_min_len(MIN_TASK_LEN)
+        .map(|corners| classify_corners(plane, corners, tolerance))
+        .collect()
+}
+
+/// Tests every triangle for being degenerate within the tolerance.
+pub(crate) fn degenerate<S: Scalar, V: Vertex<S>>(
+    triangles: &[Triangle<V>],
+    tolerance: Tolerance<S>,
+) -> Vec<bool> {
+    let corners = triangles.iter().map(corners).collect::<Vec<_>>();
+    corners
+        .into_par_iter()
+        .with_min_len(MIN_TASK_LEN)
+        .map(|corners| is_degenerate(corners, tolerance))
+        .collect()
+}
+
+/// Splits the triangles in chunks across threads, appending the pieces to `split` in order.
+///
+/// Hands the triangles back if the constructor cant be forked.
+pub(crate) fn split<S: Scalar, V: Vertex<S> + Clone>(
+    triangles: Vec<Triangle<V>>,
+    plane: Plane<S>,
+    keep: Option<Keep>,
+    tolerance: Tolerance<S>,
+    ctor: &impl VertexConstructor<V, S>,
+    split: &mut Split<V>,
+) -> Result<(), Vec<Triangle<V>>> {
+    let chunks = triangles.len().div_ceil(MIN_TASK_LEN);
+    let ctors = match (0..chunks).map(|_| ctor.fork()).collect::<Option<Vec<_>>>() {
+        Some(ctors) => ctors,
+        None => return Err(triangles),
+    };
+    let pieces = triangles
+        .into_par_iter()
+        .chunks(MIN_TASK_LEN)
+        .zip(ctors)
+        .map(|(chunk, mut ctor)| {
+            let mut pieces = Split::new(Vec::new(), Vec::new(), Vec::new());
+            split_serial(chunk, plane, keep, tolerance, &mut ctor, &mut pieces);
+            pieces
+        })
+        .collect::<Vec<_>>();
+    for pieces in pieces {
+        split.append(pieces);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MIN_TASK_LEN;
+    use crate::testing::{sphere, V};
+    use crate::{
+        slice, slice_with, CapConstructor, Plane, SliceOptions, SliceResult, TextureBounds,
+        Triangle, Vertex,
+    };
+
+    fn bits(triangles: &[Triangle<V>]) -> Vec<u32> {
+        triangles
+            .iter()
+            .flat_map(|t| vec![&t.a, &t.b, &t.c])
+            .flat_map(|v| v.pos.iter().chain(&v.normal).chain(&v.uv).copied())
+            .map(f32::to_bits)
+            .collect()
+    }
+
+    #[test]
+    fn same_as_serial() {
+        let triangles = sphere(128, 64);
+        assert!(triangles.len() > 8 * MIN_TASK_LEN);
+        let plane = Plane::from_pos_normal([0.1, 0.2, 0.3], [0.3, 0.2, 1.0]);
+        let (tb, options) = (TextureBounds::default(), SliceOptions::default());
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let (parallel, serial) = pool.install(|| {
+            // the closure cant be forked, so it keeps the splitting on this thread
+            let mut serial =
+                CapConstructor(|cut: &V, _, attributes| V::new_cap(cut.pos(), attributes));
+            (
+                slice(triangles.clone(), plane, tb, options),
+                slice_with(triangles, plane, tb, options, &mut serial),
+            )
+        });
+        match (parallel, serial) {
+            (SliceResult::Sliced(pu, pl), SliceResult::Sliced(su, sl)) => {
+                assert!(!pu.cross_section.is_empty());
+                assert_eq!(bits(&pu.hull), bits(&su.hull));
+                assert_eq!(bits(&pl.hull), bits(&sl.hull));
+                assert_eq!(bits(&pu.cross_section), bits(&su.cross_section));
+                assert_eq!(bits(&pl.cross_section), bits(&sl.cross_section));
+            }
+            _ => panic!("the plane cuts through the sphere"),
+        }
+    }
+}
@@ -1,64 +1,267 @@
-use crate::math::{cross, dot_v3, normalized, sub_v3};
-use crate::EPSILON;
+use crate::math::{
+    add_v3, cross, dot_v3, magnitude, magnitude_squared, negate_v3, normalized, scale_v3, sub_v3,
+};
+use crate::options::Tolerance;
+use crate::{Scalar, SliceError};
 
-#[derive(Copy, Clone)]
-pub struct Plane {
-    normal: [f32; 3],
-    dist: f32,
+/// A plane made of a normal and a distance, containing every point `p` with `dot(normal, p) == dist`.
+///
+/// The side the normal points to is [`Side::Above`], where [`Plane::signed_distance`] is positive.
+/// Every constructor and query follows this convention. The normal does not have to be of unit
+/// length, but then distances are scaled by its length.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane<S = f32> {
+    normal: [S; 3],
+    dist: S,
 }
 
-impl Plane {
+impl<S: Scalar> Plane<S> {
+    /// Creates the plane of the points `p` with `dot(normal, p) == dist`.
     #[inline(always)]
-    pub fn new(normal: [f32; 3], dist: f32) -> Self {
+    pub fn new(normal: [S; 3], dist: S) -> Self {
         Plane { normal, dist }
     }
 
+    /// Creates the plane through `pos` facing towards `normal`.
     #[inline(always)]
-    pub fn from_pos_normal(pos: [f32; 3], normal: [f32; 3]) -> Self {
+    pub fn from_pos_normal(pos: [S; 3], normal: [S; 3]) -> Self {
         Plane {
             normal,
             dist: dot_v3(normal, pos),
         }
     }
 
+    /// Creates the plane through the three points, like [`Plane::try_from_points`] but producing a
+    /// non-finite normal for collinear points.
     #[inline(always)]
-    pub fn from_spanning_vectors(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Self {
+    pub fn from_spanning_vectors(a: [S; 3], b: [S; 3], c: [S; 3]) -> Self {
         let normal = normalized(cross(sub_v3(b, a), sub_v3(c, a)));
 
         Plane {
             normal,
-            dist: -dot_v3(normal, a),
+            dist: dot_v3(normal, a),
         }
     }
 
-    pub(crate) fn classify_side(&self, point: [f32; 3]) -> Side {
-        let res = dot_v3(self.normal, point) - self.dist;
-        if res < -EPSILON {
+    /// Creates the plane passing through the three points, with the normal facing the side from which
+    /// they appear counter clockwise.
+    ///
+    /// Fails if the points are collinear or not finite.
+    pub fn try_from_points(a: [S; 3], b: [S; 3], c: [S; 3]) -> Result<Self, SliceError> {
+        if ![a, b, c].iter().flatten().all(|x| x.is_finite()) {
+            return Err(SliceError::NonFinitePlane);
+        }
+        let normal = cross(sub_v3(b, a), sub_v3(c, a));
+        let len = magnitude(normal);
+        if !len.is_normal() {
+            return Err(SliceError::ZeroLengthNormal);
+        }
+        let normal = [normal[0] / len, normal[1] / len, normal[2] / len];
+        Ok(Plane::from_pos_normal(a, normal))
+    }
+
+    /// Checks that the plane is usable for slicing.
+    pub(crate) fn validate(&self) -> Result<(), SliceError> {
+        if !(self.normal.iter().all(|x| x.is_finite()) && self.dist.is_finite()) {
+            Err(SliceError::NonFinitePlane)
+        } else if !magnitude(self.normal).is_normal() {
+            Err(SliceError::ZeroLengthNormal)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Classifies on which side of the plane the point lies.
+    pub fn classify_side(&self, point: [S; 3]) -> Side {
+        self.classify_side_within(point, Tolerance::default())
+    }
+
+    pub(crate) fn classify_side_within(&self, point: [S; 3], tolerance: Tolerance<S>) -> Side {
+        let res = self.signed_distance(point);
+        let eps = tolerance.on_plane();
+        if res < -eps {
             Side::Below
-        } else if res > EPSILON {
+        } else if res > eps {
             Side::Above
         } else {
             Side::On
         }
     }
 
+    /// Signed distance of the point to the plane, positive above and negative below it.
+    ///
+    /// With the `robust` feature the sign is exact.
+    #[cfg(not(feature = "robust"))]
+    #[inline(always)]
+    pub fn signed_distance(&self, point: [S; 3]) -> S {
+        dot_v3(self.normal, point) - self.dist
+    }
+
+    /// Signed distance of the point to the plane, positive above and negative below it.
+    ///
+    /// With the `robust` feature the sign is exact.
+    #[cfg(feature = "robust")]
+    #[inline(always)]
+    pub fn signed_distance(&self, point: [S; 3]) -> S {
+        crate::robust::signed_distance(self.normal, self.dist, point)
+    }
+
+    /// Returns where the line through `a` and `b` crosses the plane, as a factor from `a` to `b`.
+    ///
+    /// The result is not finite if the line is parallel to the plane.
+    #[cfg(not(feature = "robust"))]
+    pub(crate) fn intersection_factor(&self, a: [S; 3], b: [S; 3]) -> S {
+        (self.dist - dot_v3(self.normal, a)) / dot_v3(self.normal, sub_v3(b, a))
+    }
+
+    /// Returns where the line through `a` and `b` crosses the plane, as a factor from `a` to `b`.
+    ///
+    /// Both end points are measured exactly, so the factor lies within the segment whenever they
+    /// are classified on opposing sides.
+    #[cfg(feature = "robust")]
+    pub(crate) fn intersection_factor(&self, a: [S; 3], b: [S; 3]) -> S {
+        let (da, db) = (self.signed_distance(a), self.signed_distance(b));
+        da / (da - db)
+    }
+
+    /// Returns the closest point on the plane.
+    #[inline]
+    pub fn project_point(&self, point: [S; 3]) -> [S; 3] {
+        let d = (dot_v3(self.normal, point) - self.dist) / magnitude_squared(self.normal);
+        sub_v3(point, scale_v3(self.normal, d))
+    }
+
+    /// Returns the same plane facing the other way, swapping what is above and below.
+    #[inline]
+    pub fn flip(self) -> Self {
+        Plane {
+            normal: negate_v3(self.normal),
+            dist: -self.dist,
+        }
+    }
+
+    /// Returns the same plane with a unit length normal.
+    #[inline]
+    pub fn normalize(self) -> Self {
+        let len = magnitude(self.normal);
+        Plane {
+            normal: scale_v3(self.normal, S::ONE / len),
+            dist: self.dist / len,
+        }
+    }
+
+    /// Transforms the plane by the affine matrix given as columns, as returned by `to_cols_array_2d`
+    /// in glam or held by a mint `ColumnMatrix4`.
+    ///
+    /// Points above the plane stay above the transformed plane. The normal of the result has unit
+    /// length, it is not finite if the matrix is not invertible. The last row of the matrix is
+    /// ignored, so projections are not supported.
+    pub fn transform(&self, matrix: [[S; 4]; 4]) -> Self {
+        let col = |i: usize| [matrix[i][0], matrix[i][1], matrix[i][2]];
+        let (x, y, z, translation) = (col(0), col(1), col(2), col(3));
+        // the rows of the inverse of the linear part are the cross products of its columns divided
+        // by the determinant, so the normal transforms by the transposed inverse like this
+        let det = dot_v3(x, cross(y, z));
+        let n = self.normal;
+        let normal = add_v3(
+            add_v3(scale_v3(cross(y, z), n[0]), scale_v3(cross(z, x), n[1])),
+            scale_v3(cross(x, y), n[2]),
+        );
+        let normal = scale_v3(normal, S::ONE / det);
+        Plane {
+            normal,
+            dist: self.dist + dot_v3(normal, translation),
+        }
+        .normalize()
+    }
+
+    /// Returns where the ray from `origin` along `direction` hits the plane.
+    ///
+    /// Returns `None` if the ray points away from the plane or runs parallel to it.
+    pub fn intersect_ray(&self, origin: [S; 3], direction: [S; 3]) -> Option<[S; 3]> {
+        let t = self.line_factor(origin, direction)?;
+        if t < S::ZERO {
+            return None;
+        }
+        Some(add_v3(origin, scale_v3(direction, t)))
+    }
+
+    /// Returns where the segment from `a` to `b` crosses the plane.
+    ///
+    /// Returns `None` if both end points lie on the same side or the segment runs parallel to the
+    /// plane.
+    pub fn intersect_segment(&self, a: [S; 3], b: [S; 3]) -> Option<[S; 3]> {
+        let direction = sub_v3(b, a);
+        let t = self.line_factor(a, direction)?;
+        if t < S::ZERO || t > S::ONE {
+            return None;
+        }
+        Some(add_v3(a, scale_v3(direction, t)))
+    }
+
+    fn line_factor(&self, origin: [S; 3], direction: [S; 3]) -> Option<S> {
+        let denom = dot_v3(self.normal, direction);
+        if !denom.is_normal() {
+            return None;
+        }
+        Some((self.dist - dot_v3(self.normal, origin)) / denom)
+    }
+
+    /// Returns the line both planes share as a point on it and its direction, the cross product of
+    /// both normals.
+    ///
+    /// Returns `None` if the planes are parallel.
+    pub fn intersect_plane(&self, other: &Self) -> Option<([S; 3], [S; 3])> {
+        let direction = cross(self.normal, other.normal);
+        let len_squared = magnitude_squared(direction);
+        if !len_squared.is_normal() {
+            return None;
+        }
+        let point = add_v3(
+            scale_v3(cross(other.normal, direction), self.dist),
+            scale_v3(cross(direction, self.normal), other.dist),
+        );
+        Some((scale_v3(point, S::ONE / len_squared), direction))
+    }
+
+    /// Returns the point all three planes share.
+    ///
+    /// Returns `None` if two of the planes are parallel or all three share a line.
+    pub fn intersect_planes(&self, b: &Self, c: &Self) -> Option<[S; 3]> {
+        let bc = cross(b.normal, c.normal);
+        let det = dot_v3(self.normal, bc);
+        if !det.is_normal() {
+            return None;
+        }
+        let point = add_v3(
+            add_v3(
+                scale_v3(bc, self.dist),
+                scale_v3(cross(c.normal, self.normal), b.dist),
+            ),
+            scale_v3(cross(self.normal, b.normal), c.dist),
+        );
+        Some(scale_v3(point, S::ONE / det))
+    }
+
+    /// Returns the offset of the plane from the origin along its normal, scaled by the normal's
+    /// length.
     #[inline(always)]
-    pub fn dist(&self) -> f32 {
+    pub fn dist(&self) -> S {
         self.dist
     }
 
     #[inline(always)]
-    pub fn normal(&self) -> [f32; 3] {
+    pub fn normal(&self) -> [S; 3] {
         self.normal
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub(crate) enum Side {
-    // On the plane
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// On the plane
     On,
-    // above the plane(aka the side where the normal points)
+    /// above the plane(aka the side where the normal points)
     Above,
-    // below the plane(against the normal)
+    /// below the plane(against the normal)
     Below,
 }
@@ -0,0 +1,337 @@
+use crate::math::{cross, dot_v3, magnitude_squared, normalized, sub_v3};
+use crate::triangulate::plane_basis;
+use crate::{Plane, Scalar, TextureBounds};
+
+/// How the cross section is projected onto the [`TextureBounds`].
+///
+/// All projections except [`UvProjection::Box`] map onto two axes spanning the cutting plane, which
+/// can be oriented with [`TextureBounds::with_tangent`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UvProjection<S = f32> {
+    /// Stretches the bounding box of the cross section onto the texture bounds, distorting the texture
+    /// unless both have the same aspect ratio.
+    Stretch,
+    /// Scales the cross section uniformly so its bounding box fits into the texture bounds, centred
+    /// along its shorter side.
+    Fit,
+    /// Projects the world space position onto the plane axes, with the texture bounds covering `size`
+    /// world units along each axis.
+    ///
+    /// The texel density is the same for every cut and the texture lines up across cuts through the
+    /// same plane, coordinates outside of the bounds are meant to be wrapped by a repeating sampler.
+    World { size: S },
+    /// Projects the world space position onto the coordinate plane facing the cutting plane the most,
+    /// like a box or triplanar mapped material, with the texture bounds covering `size` world units.
+    ///
+    /// The cut faces then line up with the rest of a box mapped mesh.
+    Box { size: S },
+}
+
+impl<S> Default for UvProjection<S> {
+    #[inline]
+    fn default() -> Self {
+        UvProjection::Stretch
+    }
+}
+
+/// The affine map from positions on a cap to its texture coordinates.
+pub(crate) struct UvMap<S> {
+    /// the world space directions of the projected u and v coordinates
+    axes: [[S; 3]; 2],
+    /// the projected coordinates mapped to the origin of the texture bounds
+    origin: [S; 2],
+    /// the scale from projected coordinates to the texture bounds
+    scale: [S; 2],
+    /// the origin of the texture bounds
+    bias: [S; 2],
+}
+
+impl<S: Scalar> UvMap<S> {
+    /// Sets up the projection of the cap made of the given points.
+    pub fn new(
+        plane: Plane<S>,
+        tb: &TextureBounds<S>,
+        points: impl IntoIterator<Item = [S; 3]>,
+    ) -> Self {
+        let axes = match tb.projection {
+            UvProjection::Box { .. } => box_axes(plane.normal()),
+            _ => plane_axes(plane, tb.tangent),
+        };
+        let [u_axis, v_axis] = axes;
+
+        let mut min = [S::MAX; 2];
+        let mut max = [S::MIN; 2];
+        if let UvProjection::Stretch | UvProjection::Fit = tb.projection {
+            for p in points {
+                let (u, v) = (dot_v3(p, u_axis), dot_v3(p, v_axis));
+                min = [min[0].min(u), min[1].min(v)];
+                max = [max[0].max(u), max[1].max(v)];
+            }
+        }
+        let [width, height] = [max[0] - min[0], max[1] - min[1]];
+
+        let (origin, scale) = match tb.projection {
+            UvProjection::Stretch => (min, [S::ONE / width, S::ONE / height]),
+            UvProjection::Fit => {
+                let scale = S::ONE / width.max(height);
+                // centre the bounding box within the unit square
+                let half = S::from_f64(0.5);
+                let origin = [
+                    min[0] + half * (width - S::ONE / scale),
+                    min[1] + half * (height - S::ONE / scale),
+                ];
+                (origin, [scale; 2])
+            }
+            UvProjection::World { size } | UvProjection::Box { size } => {
+                ([S::ZERO; 2], [S::ONE / size; 2])
+            }
+        };
+
+        let [dx, dy] = [tb.x_max - tb.x_min, tb.y_max - tb.y_min];
+        UvMap {
+            axes,
+            origin,
+            scale: [scale[0] * dx, scale[1] * dy],
+            bias: [tb.x_min, tb.y_min],
+        }
+    }
+
+    /// Returns the texture coordinates of the given position.
+    #[inline]
+    pub fn uv(&self, pos: [S; 3]) -> [S; 2] {
+        let [u_axis, v_axis] = self.axes;
+        [
+            (dot_v3(pos, u_axis) - self.origin[0]) * self.scale[0] + self.bias[0],
+            (dot_v3(pos, v_axis) - self.origin[1]) * self.scale[1] + self.bias[1],
+        ]
+    }
+
+    /// Calculates the unit vectors in the plane with the given normal along which the u and v texture
+    /// coordinates grow.
+    pub fn frame(&self, normal: [S; 3]) -> ([S; 3], [S; 3]) {
+        let nn = dot_v3(normal, normal);
+        let in_plane = |axis: [S; 3], scale: S| {
+            let g = [axis[0] * scale, axis[1] * scale, axis[2] * scale];
+            let d = dot_v3(normal, g) / nn;
+            sub_v3(g, [normal[0] * d, normal[1] * d, normal[2] * d])
+        };
+        let grad_u = in_plane(self.axes[0], self.scale[0]);
+        let grad_v = in_plane(self.axes[1], self.scale[1]);
+
+        // the tangent keeps v constant, so it is the part of the u gradient perpendicular to the v
+        // gradient, and vice versa for the bitangent
+        let perpendicular = |a: [S; 3], b: [S; 3]| {
+            let d = dot_v3(a, b) / dot_v3(b, b);
+            let v = sub_v3(a, [b[0] * d, b[1] * d, b[2] * d]);
+            if magnitude_squared(v).is_normal() {
+                normalized(v)
+            } else {
+                v
+            }
+        };
+        (perpendicular(grad_u, grad_v), perpendicular(grad_v, grad_u))
+    }
+}
+
+/// The space the caps are textured in, when the mesh is sliced in another space.
+pub(crate) struct CapSpace<S> {
+    /// the columns of the affine transform into the texture space
+    matrix: [[S; 4]; 4],
+    /// the rows of the inverse of the linear part of `matrix`
+    inverse: [[S; 3]; 3],
+    /// the cutting plane in texture space
+    pub plane: Plane<S>,
+}
+
+impl<S: Scalar> CapSpace<S> {
+    /// Returns `None` if the matrix has no inverse, including matrices that arent finite.
+    pub fn new(matrix: [[S; 4]; 4], plane: Plane<S>) -> Option<Self> {
+        let [x, y, z] = [0, 1, 2].map(|i| [matrix[i][0], matrix[i][1], matrix[i][2]]);
+        let det = dot_v3(x, cross(y, z));
+        if !det.is_normal() || matrix[3].iter().any(|x| !x.is_finite()) {
+            return None;
+        }
+        let inverse = [cross(y, z), cross(z, x), cross(x, y)]
+            .map(|row| [row[0] / det, row[1] / det, row[2] / det]);
+        Some(CapSpace {
+            matrix,
+            inverse,
+            plane,
+        })
+    }
+
+    /// Returns the cutting plane in the space of the mesh.
+    pub fn local_plane(&self) -> Plane<S> {
+        // points transform by the matrix, so the plane transforms by its transpose
+        let normal = self.plane.normal();
+        let [x, y, z, translation] =
+            [0, 1, 2, 3].map(|i| [self.matrix[i][0], self.matrix[i][1], self.matrix[i][2]]);
+        Plane::new(
+            [dot_v3(x, normal), dot_v3(y, normal), dot_v3(z, normal)],
+            self.plane.dist() - dot_v3(normal, translation),
+        )
+        .normalize()
+    }
+
+    /// Transforms a position of the mesh into texture space.
+    pub fn pos(&self, [x, y, z]: [S; 3]) -> [S; 3] {
+        let m = &self.matrix;
+        [0, 1, 2].map(|i| m[0][i] * x + m[1][i] * y + m[2][i] * z + m[3][i])
+    }
+
+    /// Transforms a direction in texture space back into the space of the mesh, normalizing it.
+    pub fn local_direction(&self, direction: [S; 3]) -> [S; 3] {
+        let local = self.inverse.map(|row| dot_v3(row, direction));
+        if magnitude_squared(local).is_normal() {
+            normalized(local)
+        } else {
+            local
+        }
+    }
+}
+
+/// The axes spanning the plane, with the u axis along the projection of `tangent` if given.
+fn plane_axes<S: Scalar>(plane: Plane<S>, tangent: Option<[S; 3]>) -> [[S; 3]; 2] {
+    let normal = plane.normal();
+    let u_axis = tangent
+        .map(|tangent| {
+            let d = dot_v3(normal, tangent) / dot_v3(normal, normal);
+            sub_v3(tangent, [normal[0] * d, normal[1] * d, normal[2] * d])
+        })
+        .filter(|&u| magnitude_squared(u).is_normal())
+        .unwrap_or_else(|| plane_basis(plane).0);
+    let u_axis = normalized(u_axis);
+    // same orientation as the default basis, v = u x n
+    [u_axis, normalized(cross(u_axis, normal))]
+}
+
+/// The world axes spanning the coordinate plane the normal is the most perpendicular to.
+fn box_axes<S: Scalar>([x, y, z]: [S; 3]) -> [[S; 3]; 2] {
+    let (o, i) = (S::ZERO, S::ONE);
+    if x.abs() >= y.abs() && x.abs() >= z.abs() {
+        [[o, i, o], [o, o, i]]
+    } else if y.abs() >= z.abs() {
+        [[o, o, i], [i, o, o]]
+    } else {
+        [[i, o, o], [o, i, o]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{add_v3, cross, dot_v3, normalized, scale_v3, sub_v3};
+    use crate::testing::{cube, face_normal, V};
+    use crate::{
+        slice_convex_transformed, Plane, SliceError, SliceOptions, TextureBounds, Triangle,
+    };
+
+    /// Transforms a point of the mesh into world space.
+    fn transform(matrix: [[f32; 4]; 4], pos: [f32; 3]) -> [f32; 3] {
+        [0, 1, 2].map(|i| (0..3).map(|j| matrix[j][i] * pos[j]).sum::<f32>() + matrix[3][i])
+    }
+
+    /// Transforms a normal of the mesh into world space, by the inverse transpose of the matrix.
+    fn transform_normal(matrix: [[f32; 4]; 4], normal: [f32; 3]) -> [f32; 3] {
+        let [x, y, z] = [0, 1, 2].map(|i| [matrix[i][0], matrix[i][1], matrix[i][2]]);
+        let det = dot_v3(x, cross(y, z));
+        let rows = [cross(y, z), cross(z, x), cross(x, y)];
+        normalized(
+            (0..3)
+                .map(|i| scale_v3(rows[i], normal[i] / det))
+                .fold([0.0; 3], add_v3),
+        )
+    }
+
+    fn centroid<'a>(triangles: impl IntoIterator<Item = &'a Triangle<V>>) -> [f32; 3] {
+        let (sum, count) = triangles
+            .into_iter()
+            .flat_map(|t| vec![t.a.pos, t.b.pos, t.c.pos])
+            .fold(([0.0; 3], 0.0), |(sum, count), pos| {
+                (add_v3(sum, pos), count + 1.0)
+            });
+        scale_v3(sum, 1.0 / count)
+    }
+
+    /// Cuts the unit cube placed in the world by the matrix and checks that the caps face away from
+    /// their part in object space, while carrying the world plane normal as documented.
+    fn assert_caps_face_away(matrix: [[f32; 4]; 4]) {
+        let normal = normalized([0.3, -0.2, 1.0]);
+        let plane = Plane::from_pos_normal(transform(matrix, [0.1, 0.2, 0.3]), normal);
+        let (upper, lower) = slice_convex_transformed(
+            cube(),
+            matrix,
+            plane,
+            TextureBounds::default(),
+            SliceOptions::default(),
+        )
+        .unwrap()
+        .sliced()
+        .expect("the plane cuts through the cube");
+
+        for (part, side) in [(&upper, 1.0), (&lower, -1.0)].iter() {
+            assert!(!part.cross_section.is_empty());
+            let center = centroid(&part.hull);
+            for t in &part.cross_section {
+                let outwards = sub_v3(centroid(Some(t)), center);
+                assert!(dot_v3(face_normal(t), outwards) > 0.0);
+                for v in &[&t.a, &t.b, &t.c] {
+                    let world = transform_normal(matrix, v.normal);
+                    assert!(dot_v3(world, scale_v3(normal, *side)) > 0.999);
+                }
+            }
+            // the upper part lies above the world plane
+            let world_center = transform(matrix, center);
+            assert!((dot_v3(world_center, normal) - plane.dist()) * side > 0.0);
+        }
+    }
+
+    #[test]
+    fn non_uniform_scale() {
+        assert_caps_face_away([
+            [3.0, 0.0, 0.0, 0.0],
+            [0.0, 0.5, 0.0, 0.0],
+            [0.0, 0.0, 1.5, 0.0],
+            [1.0, -2.0, 0.5, 1.0],
+        ]);
+    }
+
+    #[test]
+    fn rotation() {
+        let (sin, cos) = 0.7f32.sin_cos();
+        assert_caps_face_away([
+            [cos, sin, 0.0, 0.0],
+            [-sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 4.0, -1.0, 1.0],
+        ]);
+    }
+
+    #[test]
+    fn reflection() {
+        assert_caps_face_away([
+            [-1.0, 0.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.5, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    #[test]
+    fn singular() {
+        let flat = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let plane = Plane::from_pos_normal([0.0; 3], [1.0, 0.0, 0.0]);
+        let result = slice_convex_transformed(
+            cube(),
+            flat,
+            plane,
+            TextureBounds::default(),
+            SliceOptions::default(),
+        );
+        assert_eq!(result.err(), Some(SliceError::SingularTransform));
+    }
+}
@@ -0,0 +1,55 @@
+//! Exact plane distances for the `robust` feature.
+//!
+//! Every product is split into its rounded value and the rounding error with a fused multiply add,
+//! after which the sum is carried out exactly as an expansion of non-overlapping components.
+
+use crate::Scalar;
+
+/// Sums two floats without error, `a + b == x + y` holds exactly.
+#[inline(always)]
+fn two_sum<S: Scalar>(a: S, b: S) -> (S, S) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+/// Multiplies two floats without error, `a * b == x + y` holds exactly.
+#[inline(always)]
+fn two_product<S: Scalar>(a: S, b: S) -> (S, S) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// Signed distance of the point to the plane given by `normal` and `dist`.
+///
+/// The sign of the result is always the sign of the exact distance, its magnitude is the exact
+/// distance rounded.
+pub(crate) fn signed_distance<S: Scalar>(normal: [S; 3], dist: S, point: [S; 3]) -> S {
+    let (x, x_err) = two_product(normal[0], point[0]);
+    let (y, y_err) = two_product(normal[1], point[1]);
+    let (z, z_err) = two_product(normal[2], point[2]);
+    let terms = [x_err, y_err, z_err, x, y, z, -dist];
+    // components ordered by increasing magnitude, zeroes are dropped
+    let mut expansion = [S::ZERO; 7];
+    let mut len = 0;
+    for &term in &terms {
+        let mut sum = term;
+        let mut next = 0;
+        for i in 0..len {
+            let (s, err) = two_sum(sum, expansion[i]);
+            if err != S::ZERO {
+                expansion[next] = err;
+                next += 1;
+            }
+            sum = s;
+        }
+        if sum != S::ZERO {
+            expansion[next] = sum;
+            next += 1;
+        }
+        len = next;
+    }
+    // the largest component dominates the smaller ones, so it decides the sign
+    expansion[..len].iter().copied().sum()
+}
@@ -0,0 +1,115 @@
+use std::fmt::Debug;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
+
+/// Floating point type the positions, texture coordinates and planes are made of.
+///
+/// This is implemented for `f32` and `f64`.
+pub trait Scalar:
+    Copy
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + Sum
+    + Send
+    + Sync
+    + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+    /// The absolute tolerance used by default, see [`SliceOptions`](crate::SliceOptions).
+    const DEFAULT_TOLERANCE: Self;
+
+    /// Converts a constant, rounding it if it isnt representable.
+    fn from_f64(value: f64) -> Self;
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn floor(self) -> Self;
+    fn to_f64(self) -> f64;
+    /// Computes `self * a + b` with a single rounding.
+    fn mul_add(self, a: Self, b: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn is_finite(self) -> bool;
+    fn is_normal(self) -> bool;
+    /// The raw bits of this value, usable for hashing.
+    fn to_bits(self) -> u64;
+}
+
+macro_rules! impl_scalar {
+    ($ty:ident, $tolerance:expr) => {
+        impl Scalar for $ty {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const MIN: Self = $ty::MIN;
+            const MAX: Self = $ty::MAX;
+            const DEFAULT_TOLERANCE: Self = $tolerance;
+
+            #[inline(always)]
+            fn from_f64(value: f64) -> Self {
+                value as $ty
+            }
+
+            #[inline(always)]
+            fn sqrt(self) -> Self {
+                $ty::sqrt(self)
+            }
+
+            #[inline(always)]
+            fn abs(self) -> Self {
+                $ty::abs(self)
+            }
+
+            #[inline(always)]
+            fn floor(self) -> Self {
+                $ty::floor(self)
+            }
+
+            #[inline(always)]
+            fn to_f64(self) -> f64 {
+                self.into()
+            }
+
+            #[inline(always)]
+            fn mul_add(self, a: Self, b: Self) -> Self {
+                $ty::mul_add(self, a, b)
+            }
+
+            #[inline(always)]
+            fn min(self, other: Self) -> Self {
+                $ty::min(self, other)
+            }
+
+            #[inline(always)]
+            fn max(self, other: Self) -> Self {
+                $ty::max(self, other)
+            }
+
+            #[inline(always)]
+            fn is_finite(self) -> bool {
+                $ty::is_finite(self)
+            }
+
+            #[inline(always)]
+            fn is_normal(self) -> bool {
+                $ty::is_normal(self)
+            }
+
+            #[inline(always)]
+            fn to_bits(self) -> u64 {
+                $ty::to_bits(self).into()
+            }
+        }
+    };
+}
+
+impl_scalar!(f32, 1e-7);
+impl_scalar!(f64, 1e-12);
@@ -0,0 +1,427 @@
+use std::collections::HashSet;
+use std::mem;
+
+use crate::math::pos_key;
+use crate::options::Tolerance;
+use crate::projection::CapSpace;
+use crate::triangulate::{triangulate, HullBuffers};
+use crate::{
+    classify_triangles, corners, is_degenerate, remove_degenerate, split_into, split_triangle,
+    unsliced_outcome, NewVertex, Plane, Scalar, Side, SliceOptions, Split, SubMesh, TextureBounds,
+    Triangle, Vertex, VertexConstructor,
+};
+
+/// What [`Slicer::slice_into`] found, like the variants of [`SliceResult`](crate::SliceResult)
+/// without the triangles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SliceOutcome {
+    /// The plane cut through the mesh, the upper and lower output hold the halves.
+    Sliced,
+    /// The mesh lies above the plane, possibly touching it, and was copied to the upper hull.
+    AllAbove,
+    /// The mesh lies below the plane, possibly touching it, and was copied to the lower hull.
+    AllBelow,
+    /// Every triangle of the mesh lies on the plane, it was copied to the upper hull.
+    Coplanar,
+    /// The mesh has no triangles.
+    Empty,
+}
+
+/// One side of a mesh sliced by [`Slicer::slice_borrowed_into`], referring to the triangles the plane
+/// did not cut by their index instead of copying them.
+#[derive(Clone)]
+pub struct BorrowedSubMesh<V> {
+    /// The indices of the sliced triangles that lie on this side as a whole, in ascending order.
+    pub untouched: Vec<usize>,
+    /// The pieces of the triangles the plane cut through.
+    pub split: Vec<Triangle<V>>,
+    /// The cap closing this side along the cut, empty if the plane did not cut through the mesh.
+    pub cross_section: Vec<Triangle<V>>,
+}
+
+impl<V> BorrowedSubMesh<V> {
+    /// Returns the triangles of the hull, the untouched ones looked up in the sliced `triangles`
+    /// followed by the split ones.
+    pub fn hull<'a>(
+        &'a self,
+        triangles: &'a [Triangle<V>],
+    ) -> impl Iterator<Item = &'a Triangle<V>> + 'a {
+        self.untouched
+            .iter()
+            .map(move |&index| &triangles[index])
+            .chain(&self.split)
+    }
+
+    /// Whether the hull has no triangles.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.untouched.is_empty() && self.split.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.untouched.clear();
+        self.split.clear();
+        self.cross_section.clear();
+    }
+}
+
+impl<V> Default for BorrowedSubMesh<V> {
+    #[inline]
+    fn default() -> Self {
+        BorrowedSubMesh {
+            untouched: Vec::new(),
+            split: Vec::new(),
+            cross_section: Vec::new(),
+        }
+    }
+}
+
+/// Slices convex meshes like [`slice_convex`](crate::slice_convex), keeping its scratch buffers
+/// around between calls.
+///
+/// Together with output meshes that are reused as well, slicing stops allocating once the buffers
+/// have grown large enough, unless the `parallel` feature is enabled.
+pub struct Slicer<V, S = f32> {
+    segments: Vec<[V; 2]>,
+    coplanar: Vec<Triangle<V>>,
+    coplanar_indices: Vec<usize>,
+    seen: HashSet<[u64; 3]>,
+    hull: HullBuffers<V, S>,
+}
+
+impl<V, S> Slicer<V, S> {
+    pub fn new() -> Self {
+        Slicer {
+            segments: Vec::new(),
+            coplanar: Vec::new(),
+            coplanar_indices: Vec::new(),
+            seen: HashSet::new(),
+            hull: HullBuffers::new(),
+        }
+    }
+}
+
+impl<V, S> Default for Slicer<V, S> {
+    #[inline]
+    fn default() -> Self {
+        Slicer::new()
+    }
+}
+
+impl<S: Scalar, V: Vertex<S> + Clone> Slicer<V, S> {
+    /// Slices the triangles along the plane, replacing the contents of `upper` and `lower`.
+    ///
+    /// If the plane does not cut through the mesh, the triangles are copied to the hull of the side
+    /// they lie on as told by the returned outcome.
+    pub fn slice_into(
+        &mut self,
+        triangles: &[Triangle<V>],
+        plane: Plane<S>,
+        texture_bounds: TextureBounds<S>,
+        options: SliceOptions<S>,
+        upper: &mut SubMesh<V>,
+        lower: &mut SubMesh<V>,
+    ) -> SliceOutcome {
+        self.slice_into_with(
+            triangles,
+            plane,
+            texture_bounds,
+            options,
+            upper,
+            lower,
+            &mut NewVertex,
+        )
+    }
+
+    /// Like [`Slicer::slice_into`], but creates the new vertices with the given constructor.
+    #[allow(clippy::too_many_arguments)]
+    pub fn slice_into_with(
+        &mut self,
+        triangles: &[Triangle<V>],
+        plane: Plane<S>,
+        texture_bounds: TextureBounds<S>,
+        options: SliceOptions<S>,
+        upper: &mut SubMesh<V>,
+        lower: &mut SubMesh<V>,
+        ctor: &mut impl VertexConstructor<V, S>,
+    ) -> SliceOutcome {
+        let tolerance = options.resolve(triangles);
+        self.slice_in(
+            triangles.iter().cloned(),
+            plane,
+            &texture_bounds,
+            tolerance,
+            None,
+            [upper, lower],
+            ctor,
+        )
+    }
+
+    /// Slices the triangles along the plane like [`Slicer::slice_into`], but only clones the
+    /// triangles the plane cuts through.
+    ///
+    /// The other triangles are handed back as indices into `triangles`, which makes slicing cheaper
+    /// for heavy vertices when most of the mesh lies away from the plane.
+    pub fn slice_borrowed_into(
+        &mut self,
+        triangles: &[Triangle<V>],
+        plane: Plane<S>,
+        texture_bounds: TextureBounds<S>,
+        options: SliceOptions<S>,
+        upper: &mut BorrowedSubMesh<V>,
+        lower: &mut BorrowedSubMesh<V>,
+    ) -> SliceOutcome {
+        self.slice_borrowed_into_with(
+            triangles,
+            plane,
+            texture_bounds,
+            options,
+            upper,
+            lower,
+            &mut NewVertex,
+        )
+    }
+
+    /// Like [`Slicer::slice_borrowed_into`], but creates the new vertices with the given
+    /// constructor.
+    #[allow(clippy::too_many_arguments)]
+    pub fn slice_borrowed_into_with(
+        &mut self,
+        triangles: &[Triangle<V>],
+        plane: Plane<S>,
+        texture_bounds: TextureBounds<S>,
+        options: SliceOptions<S>,
+        upper: &mut BorrowedSubMesh<V>,
+        lower: &mut BorrowedSubMesh<V>,
+        ctor: &mut impl VertexConstructor<V, S>,
+    ) -> SliceOutcome {
+        let tolerance = options.resolve(triangles);
+        upper.clear();
+        lower.clear();
+        self.segments.clear();
+
+        // the pieces are split straight into the output
+        let mut split = Split::new(
+            mem::take(&mut upper.split),
+            mem::take(&mut lower.split),
+            mem::take(&mut self.segments),
+        );
+        for (index, (triangle, sides)) in
+            classify_triangles(triangles, plane, tolerance).enumerate()
+        {
+            match split_triangle(triangle, sides, plane, None, tolerance, ctor, &mut split) {
+                Some(Side::Above) => upper.untouched.push(index),
+                Some(Side::Below) => lower.untouched.push(index),
+                Some(Side::On) => self.coplanar_indices.push(index),
+                None => (),
+            }
+        }
+        if !self.coplanar_indices.is_empty() {
+            for index in self.coplanar_indices.drain(..) {
+                match split.coplanar_hull(plane, corners(&triangles[index]), None) {
+                    Some(Side::Below) => lower.untouched.push(index),
+                    _ => upper.untouched.push(index),
+                }
+            }
+            upper.untouched.sort_unstable();
+            lower.untouched.sort_unstable();
+        }
+        upper.split = split.upper;
+        lower.split = split.lower;
+        self.segments = split.segments;
+
+        if tolerance.remove_degenerate {
+            for output in [&mut *upper, &mut *lower].iter_mut() {
+                output
+                    .untouched
+                    .retain(|&index| !is_degenerate(corners(&triangles[index]), tolerance));
+                remove_degenerate(&mut output.split, tolerance);
+            }
+        }
+
+        if upper.is_empty() || lower.is_empty() {
+            // no slicing occured
+            return unsliced_outcome(
+                plane,
+                upper.hull(triangles).map(corners),
+                lower.is_empty(),
+                tolerance,
+            );
+        }
+
+        self.cap(
+            plane,
+            &texture_bounds,
+            None,
+            ctor,
+            [&mut upper.cross_section, &mut lower.cross_section],
+        );
+        SliceOutcome::Sliced
+    }
+
+    /// Slices the triangles, replacing the contents of the upper and lower output and texturing the
+    /// caps in `space` if given.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn slice_in(
+        &mut self,
+        triangles: impl IntoIterator<Item = Triangle<V>>,
+        plane: Plane<S>,
+        texture_bounds: &TextureBounds<S>,
+        tolerance: Tolerance<S>,
+        space: Option<&CapSpace<S>>,
+        [upper, lower]: [&mut SubMesh<V>; 2],
+        ctor: &mut impl VertexConstructor<V, S>,
+    ) -> SliceOutcome {
+        for output in [&mut *upper, &mut *lower].iter_mut() {
+            output.hull.clear();
+            output.cross_section.clear();
+        }
+        self.segments.clear();
+
+        // the hulls are split straight into the output
+        let mut split = Split::new(
+            mem::take(&mut upper.hull),
+            mem::take(&mut lower.hull),
+            mem::take(&mut self.segments),
+        );
+        split.coplanar = mem::take(&mut self.coplanar);
+        split_into(triangles, plane, None, tolerance, ctor, &mut split);
+        upper.hull = split.upper;
+        lower.hull = split.lower;
+        self.segments = split.segments;
+        self.coplanar = split.coplanar;
+
+        if upper.hull.is_empty() || lower.hull.is_empty() {
+            // no slicing occured
+            return unsliced_outcome(
+                plane,
+                upper.hull.iter().map(corners),
+                lower.hull.is_empty(),
+                tolerance,
+            );
+        }
+
+        self.cap(
+            plane,
+            texture_bounds,
+            space,
+            ctor,
+            [&mut upper.cross_section, &mut lower.cross_section],
+        );
+        SliceOutcome::Sliced
+    }
+
+    /// Triangulates the cut gathered while splitting into the upper and lower cap.
+    fn cap(
+        &mut self,
+        plane: Plane<S>,
+        texture_bounds: &TextureBounds<S>,
+        space: Option<&CapSpace<S>>,
+        ctor: &mut impl VertexConstructor<V, S>,
+        [upper, lower]: [&mut Vec<Triangle<V>>; 2],
+    ) {
+        // every cut point is shared by two segments, only hand each one to the triangulation once
+        let seen = &mut self.seen;
+        seen.clear();
+        let cross = self
+            .segments
+            .drain(..)
+            .flat_map(IntoIterator::into_iter)
+            .filter(|v| seen.insert(pos_key(v.pos())));
+        // only fails if we didnt gather enough vertices to form a triangle, leaving the caps empty
+        triangulate(
+            cross,
+            plane,
+            texture_bounds,
+            space,
+            ctor,
+            &mut self.hull,
+            [lower, upper],
+        );
+    }
+}
+
+// spreading the work across threads allocates, so the guarantee only holds without `parallel`
+#[cfg(all(test, not(feature = "parallel")))]
+mod tests {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use crate::testing::{cube, V};
+    use crate::{
+        BorrowedSubMesh, Plane, SliceOptions, SliceOutcome, Slicer, SubMesh, TextureBounds,
+    };
+
+    /// Counts the allocations of each thread, so tests running alongside dont interfere.
+    struct Counting;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn count() {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count();
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count();
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    fn allocations(f: impl FnOnce()) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        f();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    #[test]
+    fn no_allocations_once_warmed_up() {
+        let triangles = cube();
+        let planes = [
+            Plane::from_pos_normal([0.0, 0.0, 0.3], [0.0, 0.0, 1.0]),
+            Plane::from_pos_normal([0.1, -0.2, 0.0], [0.3, 1.0, -0.2]),
+            Plane::from_pos_normal([0.0, 0.0, 2.0], [0.0, 0.0, 1.0]),
+        ];
+        let (tb, options) = (TextureBounds::default(), SliceOptions::default());
+        let mut slicer = Slicer::<V>::new();
+        let (mut upper, mut lower) = (SubMesh::default(), SubMesh::default());
+        let (mut borrowed_upper, mut borrowed_lower) =
+            (BorrowedSubMesh::default(), BorrowedSubMesh::default());
+        let mut slice_all = || {
+            for &plane in &planes {
+                let outcome =
+                    slicer.slice_into(&triangles, plane, tb, options, &mut upper, &mut lower);
+                let borrowed = slicer.slice_borrowed_into(
+                    &triangles,
+                    plane,
+                    tb,
+                    options,
+                    &mut borrowed_upper,
+                    &mut borrowed_lower,
+                );
+                assert_eq!(outcome, borrowed);
+                assert_eq!(
+                    outcome == SliceOutcome::Sliced,
+                    !upper.cross_section.is_empty()
+                );
+            }
+        };
+
+        assert!(allocations(&mut slice_all) > 0);
+        assert_eq!(allocations(&mut slice_all), 0);
+    }
+}
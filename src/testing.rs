@@ -0,0 +1,68 @@
+//! Meshes and measurements shared by the unit tests.
+
+use genmesh::{Triangulate, Vertices};
+
+use crate::math::{cross, dot_v3, magnitude, normalized, sub_v3};
+use crate::{vertex_to_triangle, PosNormalUvVertex, SubMesh, Triangle};
+
+pub(crate) type V = PosNormalUvVertex;
+
+/// Texture coordinates that differ for every face of an axis aligned box.
+pub(crate) fn face_uv(normal: [f32; 3]) -> [f32; 2] {
+    [normal[0] + 2.0 * normal[1], normal[2]]
+}
+
+pub(crate) fn face_normal(t: &Triangle<V>) -> [f32; 3] {
+    normalized(cross(sub_v3(t.b.pos, t.a.pos), sub_v3(t.c.pos, t.a.pos)))
+}
+
+pub(crate) fn area(triangles: &[Triangle<V>]) -> f32 {
+    triangles
+        .iter()
+        .map(|t| magnitude(cross(sub_v3(t.b.pos, t.a.pos), sub_v3(t.c.pos, t.a.pos))) / 2.0)
+        .sum()
+}
+
+/// The volume enclosed by the hull and the caps.
+pub(crate) fn volume(mesh: &SubMesh<V>) -> f32 {
+    mesh.hull
+        .iter()
+        .chain(&mesh.cross_section)
+        .map(|t| dot_v3(t.a.pos, cross(t.b.pos, t.c.pos)) / 6.0)
+        .sum()
+}
+
+/// The flat shaded cube spanning `[-1; 1]` on every axis.
+pub(crate) fn cube() -> Vec<Triangle<V>> {
+    let cube = genmesh::generators::Cube::new();
+    vertex_to_triangle(cube.triangulate().vertices().map(|v| {
+        let normal = v.normal.into();
+        V {
+            pos: v.pos.into(),
+            normal,
+            uv: face_uv(normal),
+        }
+    }))
+    .collect()
+}
+
+/// A smooth shaded sphere of radius one with `u * v * 2` triangles.
+#[cfg(feature = "parallel")]
+pub(crate) fn sphere(u: usize, v: usize) -> Vec<Triangle<V>> {
+    let sphere = genmesh::generators::SphereUv::new(u, v);
+    vertex_to_triangle(sphere.triangulate().vertices().map(|v| V {
+        pos: v.pos.into(),
+        normal: v.normal.into(),
+        uv: [v.pos.x, v.pos.y],
+    }))
+    .collect()
+}
+
+pub(crate) fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
@@ -1,6 +1,7 @@
-use crate::math::{dot_v3, sub_v3};
+use crate::math::{dot_v3, pos_key, sub_v3};
+use crate::options::Tolerance;
 use crate::plane::Side;
-use crate::{Plane, Vertex, EPSILON};
+use crate::{Plane, Scalar, Vertex, VertexConstructor};
 
 pub enum TriangleSplit<V> {
     UpperLower {
@@ -43,6 +44,23 @@ impl<V> TriangleSplit<V> {
             }
         }
     }
+
+    /// Appends only the triangles of the given side, dropping the others.
+    #[inline]
+    pub fn append_side_to(self, side: Side, hull: &mut Vec<Triangle<V>>) {
+        match (self, side) {
+            (TriangleSplit::UpperLower { upper, .. }, Side::Above)
+            | (TriangleSplit::UpperTwoLower { upper, .. }, Side::Above)
+            | (TriangleSplit::UpperLower { lower: upper, .. }, Side::Below)
+            | (TriangleSplit::LowerTwoUpper { lower: upper, .. }, Side::Below) => hull.push(upper),
+            (TriangleSplit::LowerTwoUpper { upper: [a, b], .. }, Side::Above)
+            | (TriangleSplit::UpperTwoLower { lower: [a, b], .. }, Side::Below) => {
+                hull.push(a);
+                hull.push(b);
+            }
+            (_, Side::On) => (),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -52,24 +70,21 @@ pub struct Triangle<V> {
     pub c: V,
 }
 
-impl<V: Vertex> Triangle<V> {
+impl<V> Triangle<V> {
     pub fn new(a: V, b: V, c: V) -> Self {
         Triangle { a, b, c }
     }
 }
 
-// clean this up
-pub(crate) fn intersect_triangle<V: Vertex + Clone>(
-    plane: Plane,
+/// Splits the triangle along the plane, given the sides of the plane its corners lie on.
+pub(crate) fn intersect_triangle<S: Scalar, V: Vertex<S> + Clone>(
+    plane: Plane<S>,
     triangle: Triangle<V>,
+    [side_a, side_b, side_c]: [Side; 3],
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
 ) -> Option<([V; 2], TriangleSplit<V>)> {
-    // TODO: could return a Result<TriangleSplit<V>, Side> instead, returning the side the triangle is on
-    // would allow to remove the repeated checks in slice_convex
-    // also optimize this in general, lots of branching
     let (ta, tb, tc) = (triangle.a, triangle.b, triangle.c);
-    let side_a = plane.classify_side(ta.pos());
-    let side_b = plane.classify_side(tb.pos());
-    let side_c = plane.classify_side(tc.pos());
 
     // triangle is either fully on the plane or not touching the plan
     if side_a == side_b && side_b == side_c {
@@ -77,10 +92,11 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
     }
 
     // triangle is adjacent to plane with one side
-    if (side_a == Side::On && side_b == Side::On)
-        || (side_b == Side::On && side_c == Side::On)
-        || (side_c == Side::On && side_a == Side::On)
-    {
+    let on_count = [side_a, side_b, side_c]
+        .iter()
+        .filter(|&&side| side == Side::On)
+        .count();
+    if on_count >= 2 {
         return None;
     }
 
@@ -94,7 +110,7 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
 
     // cases in which we will gen 2 triangles due to one point lying on the plane
     if side_a == Side::On {
-        if let Some(ip) = intersect_line(plane, &tb, &tc) {
+        if let Some(ip) = intersect_line(plane, tolerance, ctor, &tb, &tc) {
             let a = Triangle::new(ta.clone(), tb, ip.clone());
             let b = Triangle::new(ta.clone(), ip.clone(), tc);
             let (lower, upper) = match side_b {
@@ -105,7 +121,7 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
             return Some(([ip, ta], TriangleSplit::UpperLower { upper, lower }));
         }
     } else if side_b == Side::On {
-        if let Some(ip) = intersect_line(plane, &ta, &tc) {
+        if let Some(ip) = intersect_line(plane, tolerance, ctor, &ta, &tc) {
             let a = Triangle::new(ta, tb.clone(), ip.clone());
             let b = Triangle::new(ip.clone(), tb.clone(), tc);
             let (lower, upper) = match side_a {
@@ -116,7 +132,7 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
             return Some(([ip, tb], TriangleSplit::UpperLower { upper, lower }));
         }
     } else if side_c == Side::On {
-        if let Some(ip) = intersect_line(plane, &ta, &tb) {
+        if let Some(ip) = intersect_line(plane, tolerance, ctor, &ta, &tb) {
             let a = Triangle::new(ta, ip.clone(), tc.clone());
             let b = Triangle::new(ip.clone(), tb, tc.clone());
             let (lower, upper) = match side_a {
@@ -129,9 +145,9 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
     // 3 triangles, we cut through two lines in these cases, so one side of the split will be a polygon with 4 edges which has to be split
     } else {
         if side_a != side_b {
-            if let Some(ip) = intersect_line(plane, &ta, &tb) {
+            if let Some(ip) = intersect_line(plane, tolerance, ctor, &ta, &tb) {
                 if side_a == side_c {
-                    if let Some(ip2) = intersect_line(plane, &tb, &tc) {
+                    if let Some(ip2) = intersect_line(plane, tolerance, ctor, &tb, &tc) {
                         let a = Triangle::new(ip.clone(), tb, ip2.clone());
                         let b = Triangle::new(ta.clone(), ip.clone(), ip2.clone());
                         let c = Triangle::new(ta, ip2.clone(), tc);
@@ -148,7 +164,7 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
                         };
                         return Some(([ip, ip2], split));
                     }
-                } else if let Some(ip2) = intersect_line(plane, &ta, &tc) {
+                } else if let Some(ip2) = intersect_line(plane, tolerance, ctor, &ta, &tc) {
                     let a = Triangle::new(ta, ip.clone(), ip2.clone());
                     let b = Triangle::new(ip.clone(), tb, tc.clone());
                     let c = Triangle::new(ip2.clone(), ip.clone(), tc);
@@ -170,8 +186,8 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
             }
         }
         // no match to have lazy logical and-ing
-        if let Some(ip) = intersect_line(plane, &tc, &ta) {
-            if let Some(ip2) = intersect_line(plane, &tc, &tb) {
+        if let Some(ip) = intersect_line(plane, tolerance, ctor, &tc, &ta) {
+            if let Some(ip2) = intersect_line(plane, tolerance, ctor, &tc, &tb) {
                 let a = Triangle::new(ip.clone(), ip2.clone(), tc.clone());
                 let b = Triangle::new(ta.clone(), ip2.clone(), ip.clone());
                 let c = Triangle::new(ta, tb, ip2.clone());
@@ -193,17 +209,42 @@ pub(crate) fn intersect_triangle<V: Vertex + Clone>(
     None
 }
 
-fn intersect_line<V: Vertex>(plane: Plane, a: &V, b: &V) -> Option<V> {
-    let line = sub_v3(b.pos(), a.pos());
+fn intersect_line<S: Scalar, V: Vertex<S> + Clone>(
+    plane: Plane<S>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+    a: &V,
+    b: &V,
+) -> Option<V> {
+    // always interpolate in the same direction, so neighbouring triangles cut their shared edge at
+    // the same position no matter their winding
+    let (a, b) = if pos_key(a.pos()) <= pos_key(b.pos()) {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    intersection_factor(plane, tolerance, a.pos(), b.pos()).map(|t| ctor.interpolated(a, b, t))
+}
+
+/// Returns the factor at which the plane cuts the segment from `a` to `b`, if it does.
+fn intersection_factor<S: Scalar>(
+    plane: Plane<S>,
+    tolerance: Tolerance<S>,
+    a: [S; 3],
+    b: [S; 3],
+) -> Option<S> {
+    let line = sub_v3(b, a);
 
     let ln = dot_v3(plane.normal(), line);
-    if ln == 0.0 {
+    if ln == S::ZERO {
         None
     } else {
-        let t = (plane.dist() - dot_v3(plane.normal(), a.pos())) / ln;
-        // clamp between ~0.0 and ~1.0 since we only want the segment
-        if t >= -EPSILON && t <= (1.0 + EPSILON) {
-            Some(V::new_interpolated(a, b, t))
+        let t = plane.intersection_factor(a, b);
+        // clamp between ~0.0 and ~1.0 since we only want the segment, the tolerance is a distance
+        // so scale it to the edge
+        let eps = tolerance.eps / ln.abs();
+        if (-eps..=(S::ONE + eps)).contains(&t) {
+            Some(t)
         } else {
             None
         }
@@ -1,26 +1,37 @@
-use crate::math::{cross, div_v2, dot_v3, negate_v3, normalized, sub_v2};
-use crate::{Plane, TextureBounds, Triangle, Vertex};
+use std::collections::{HashMap, HashSet};
 
-/// monotone chain algorithm to calculate the convex hull of the vertices
-fn monotone_chain<V: Clone>(mut vertices: Vec<(V, [f32; 2])>) -> Vec<(V, [f32; 2])> {
-    fn cross_2d(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+use crate::math::{cross, dot_v3, magnitude_squared, negate_v3, normalized, pos_key, sub_v3};
+use crate::options::Tolerance;
+use crate::plane::Side;
+use crate::projection::{CapSpace, UvMap};
+use crate::{
+    CapAttributes, Plane, Scalar, SliceError, TextureBounds, Triangle, Vertex, VertexConstructor,
+};
+
+/// monotone chain algorithm to calculate the convex hull of the vertices, draining them into `hull`
+fn monotone_chain<S: Scalar, V: Clone>(
+    vertices: &mut Vec<(V, [S; 2])>,
+    hull: &mut Vec<(V, [S; 2])>,
+) {
+    fn cross_2d<S: Scalar>(a: [S; 2], b: [S; 2], c: [S; 2]) -> S {
         (a[0] - b[0]) * (b[1] - c[1]) - (b[0] - c[0]) * (a[1] - b[1])
     }
 
     // sort by 2d projection x coord, and y coord if equal
-    vertices.sort_by(|(_, a), (_, b)| {
+    vertices.sort_unstable_by(|(_, a), (_, b)| {
         a[0].partial_cmp(&b[0])
             .unwrap_or(std::cmp::Ordering::Equal)
             .then_with(|| a[1].partial_cmp(&b[1]).unwrap_or(std::cmp::Ordering::Equal))
     });
 
-    let mut hull: Vec<(_, _)> = Vec::with_capacity(vertices.len() / 2);
+    hull.clear();
+    hull.reserve(vertices.len() / 2);
 
     // lower hull
     for mapped in vertices.iter().cloned() {
         while {
             let len = hull.len();
-            len >= 2 && cross_2d(hull[len - 2].1, hull[len - 1].1, mapped.1) <= 0.0
+            len >= 2 && cross_2d(hull[len - 2].1, hull[len - 1].1, mapped.1) <= S::ZERO
         } {
             hull.pop();
         }
@@ -29,10 +40,10 @@ fn monotone_chain<V: Clone>(mut vertices: Vec<(V, [f32; 2])>) -> Vec<(V, [f32; 2
 
     // upper hull
     let offset = hull.len() + 2;
-    for mapped in vertices.into_iter().rev() {
+    for mapped in vertices.drain(..).rev() {
         while {
             let len = hull.len();
-            len >= offset && cross_2d(hull[len - 2].1, hull[len - 1].1, mapped.1) <= 0.0
+            len >= offset && cross_2d(hull[len - 2].1, hull[len - 1].1, mapped.1) <= S::ZERO
         } {
             hull.pop();
         }
@@ -40,110 +51,919 @@ fn monotone_chain<V: Clone>(mut vertices: Vec<(V, [f32; 2])>) -> Vec<(V, [f32; 2
     }
     hull.pop(); // duplicate of hull[0]
     hull.remove(offset - 2); // duplicate of hull[offset - 1]
-    debug_assert!(hull.len() >= 3);
-    hull
-}
-
-struct BoundingBox {
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
+                             // collinear points may leave less than 3 points here
 }
 
-// Map the vertices onto the cutting plane, calculating the bounding box
-fn map_to_2d_with_bb<V: Vertex>(
-    plane: Plane,
-    vertices: Vec<V>,
-) -> (BoundingBox, Vec<(V, [f32; 2])>) {
+/// Calculates two vectors spanning the plane.
+pub(crate) fn plane_basis<S: Scalar>(plane: Plane<S>) -> ([S; 3], [S; 3]) {
     // generate the plane from the normal
     let normal = plane.normal();
-    let mut plane_u = normalized(cross(normal, [1.0, 1.0, 0.0]));
+    let mut plane_u = normalized(cross(normal, [S::ONE, S::ONE, S::ZERO]));
     // our chosen vector for the cross product might be linearly dependent on the plane normal
     // so choose a different vector that is linear independent to our former chosen one if the cross product didnt work out
-    if !plane_u.iter().copied().sum::<f32>().is_normal() {
-        plane_u = cross(normal, [0.0, 1.0, 1.0]);
+    if !plane_u.iter().copied().sum::<S>().is_normal() {
+        plane_u = cross(normal, [S::ZERO, S::ONE, S::ONE]);
     }
     let plane_v = cross(plane_u, normal);
+    (plane_u, plane_v)
+}
+
+// Map the vertices onto the cutting plane
+fn map_to_2d<S: Scalar, T>(
+    plane: Plane<S>,
+    vertices: impl IntoIterator<Item = T>,
+    pos: impl Fn(&T) -> [S; 3],
+) -> impl Iterator<Item = (T, [S; 2])> {
+    let (plane_u, plane_v) = plane_basis(plane);
+    vertices.into_iter().map(move |vertex| {
+        let v2 = [dot_v3(pos(&vertex), plane_u), dot_v3(pos(&vertex), plane_v)];
+        (vertex, v2)
+    })
+}
 
-    let mut minx = f32::MAX;
-    let mut miny = f32::MAX;
-    let mut maxx = f32::MIN;
-    let mut maxy = f32::MIN;
+/// Scratch buffers of [`triangulate`].
+pub(crate) struct HullBuffers<V, S> {
+    mapped: Vec<(V, [S; 2])>,
+    hull: Vec<(V, [S; 2])>,
+    caps: Vec<V>,
+}
 
-    let mapped = vertices
+impl<V, S> HullBuffers<V, S> {
+    pub(crate) fn new() -> Self {
+        HullBuffers {
+            mapped: Vec::new(),
+            hull: Vec::new(),
+            caps: Vec::new(),
+        }
+    }
+}
+
+/// generate the cross section mesh from the intersection points twice, for each side, appending the
+/// lower and upper cross section to the given lists
+///
+/// The texture coordinates are projected in `space` instead of the space of the vertices if given.
+/// Returns whether there were enough points to form a cross section.
+pub(crate) fn triangulate<S: Scalar, V: Vertex<S> + Clone>(
+    vertices: impl IntoIterator<Item = V>,
+    plane: Plane<S>,
+    tb: &TextureBounds<S>,
+    space: Option<&CapSpace<S>>,
+    ctor: &mut impl VertexConstructor<V, S>,
+    buffers: &mut HullBuffers<V, S>,
+    [lower_cross, upper_cross]: [&mut Vec<Triangle<V>>; 2],
+) -> bool {
+    let HullBuffers { mapped, hull, caps } = buffers;
+    mapped.clear();
+    mapped.extend(map_to_2d(plane, vertices, V::pos));
+    if mapped.len() < 3 {
+        return false;
+    }
+
+    let plane_normal = plane.normal();
+    let neg_plane_normal = negate_v3(plane.normal());
+    monotone_chain(mapped, hull);
+    if hull.len() < 3 {
+        return false;
+    }
+
+    // the caps are textured in the given space, if any
+    let uv_pos = |v: &V| space.map_or(v.pos(), |space| space.pos(v.pos()));
+    let uv_plane = space.map_or(plane, |space| space.plane);
+    let uv_map = UvMap::new(uv_plane, tb, hull.iter().map(|(v, _)| uv_pos(v)));
+    for (v, uv) in hull.iter_mut() {
+        *uv = uv_map.uv(uv_pos(v));
+    }
+    let (tangent, bitangent) = uv_map.frame(uv_plane.normal());
+    let (tangent, bitangent) = match space {
+        Some(space) => (
+            space.local_direction(tangent),
+            space.local_direction(bitangent),
+        ),
+        None => (tangent, bitangent),
+    };
+    let mut cap_vertices = |caps: &mut Vec<V>, normal| {
+        caps.clear();
+        caps.extend(hull.iter().map(|(v, uv)| {
+            let attributes = CapAttributes {
+                uv: *uv,
+                normal,
+                tangent,
+                bitangent,
+            };
+            ctor.cap_vertex(v, plane, attributes)
+        }));
+    };
+
+    // fan out from the last hull point
+    let c = hull.len() - 1;
+    cap_vertices(caps, plane_normal);
+    upper_cross.extend(
+        (0..c - 1).map(|a| Triangle::new(caps[a].clone(), caps[a + 1].clone(), caps[c].clone())),
+    );
+    // reversed winding order and normal
+    cap_vertices(caps, neg_plane_normal);
+    lower_cross.extend(
+        (0..c - 1).map(|a| Triangle::new(caps[a].clone(), caps[c].clone(), caps[a + 1].clone())),
+    );
+    caps.clear();
+    true
+}
+
+/// Chains the cut segments into closed loops by welding endpoints closer than the tolerance.
+///
+/// Chains that do not close are dropped as they cannot be capped, the returned flag tells whether
+/// all of them closed.
+fn chain_loops<S: Scalar, T: Clone>(
+    segments: Vec<[T; 2]>,
+    pos: impl Fn(&T) -> [S; 3],
+    tolerance: Tolerance<S>,
+) -> (Vec<Vec<T>>, bool) {
+    let mut points: Vec<T> = Vec::with_capacity(segments.len());
+    let mut positions: Vec<[S; 3]> = Vec::with_capacity(segments.len());
+    let mut grid: HashMap<[u64; 3], Vec<usize>> = HashMap::with_capacity(segments.len());
+    let mut edges = Vec::with_capacity(segments.len());
+    let mut seen_edges = HashSet::with_capacity(segments.len());
+
+    // the points are bucketed into cells twice as large as the tolerance, so besides their own cell
+    // only the neighbouring cells on the nearer side along each axis can hold points to weld with,
+    // without a tolerance only equal positions are welded
+    let weld_epsilon = tolerance.eps;
+    let cell_size = weld_epsilon + weld_epsilon;
+    let half = S::from_f64(0.5);
+    let neighbours = if cell_size > S::ZERO { 8 } else { 1 };
+    let mut weld = |v: T| {
+        let pos = pos(&v);
+        let (cell, step) = if cell_size > S::ZERO {
+            let mut cell = [S::ZERO; 3];
+            let mut step = [S::ZERO; 3];
+            for axis in 0..3 {
+                let scaled = pos[axis] / cell_size;
+                cell[axis] = scaled.floor();
+                step[axis] = if scaled - cell[axis] < half {
+                    -S::ONE
+                } else {
+                    S::ONE
+                };
+            }
+            (cell, step)
+        } else {
+            (pos, [S::ZERO; 3])
+        };
+        // shared cut vertices have the exact same position, so check for that first, no earlier
+        // point can be within the tolerance of a welded point lying exactly there
+        let bucket = grid.get(&pos_key(cell));
+        if let Some(&idx) = bucket.and_then(|b| b.iter().find(|&&idx| positions[idx] == pos)) {
+            return idx;
+        }
+        // the earliest point within the tolerance wins, so the result doesnt depend on the buckets
+        let welded = (0..neighbours)
+            .map(|corner: usize| {
+                let mut neighbour = cell;
+                for (axis, offset) in neighbour.iter_mut().enumerate() {
+                    if corner >> axis & 1 == 1 {
+                        *offset += step[axis];
+                    }
+                }
+                neighbour
+            })
+            .filter_map(|neighbour| grid.get(&pos_key(neighbour)))
+            .flatten()
+            .copied()
+            .filter(|&idx| {
+                magnitude_squared(sub_v3(positions[idx], pos)) <= weld_epsilon * weld_epsilon
+            })
+            .min();
+        welded.unwrap_or_else(|| {
+            positions.push(pos);
+            points.push(v);
+            let idx = positions.len() - 1;
+            grid.entry(pos_key(cell)).or_default().push(idx);
+            idx
+        })
+    };
+    for [a, b] in segments {
+        let (a, b) = (weld(a), weld(b));
+        // neighbouring triangles might report the same segment, on plane edges for example
+        if a != b && seen_edges.insert((a.min(b), a.max(b))) {
+            edges.push((a.min(b), a.max(b)));
+        }
+    }
+
+    let mut adjacency = vec![vec![]; points.len()];
+    for (idx, &(a, b)) in edges.iter().enumerate() {
+        adjacency[a].push((b, idx));
+        adjacency[b].push((a, idx));
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut loops = vec![];
+    let mut all_closed = true;
+    for start in 0..points.len() {
+        loop {
+            let mut chain = vec![start];
+            let mut current = start;
+            let closed = loop {
+                let next = adjacency[current]
+                    .iter()
+                    .find(|&&(_, edge)| !used[edge])
+                    .copied();
+                match next {
+                    Some((next, edge)) => {
+                        used[edge] = true;
+                        if next == start {
+                            break true;
+                        }
+                        chain.push(next);
+                        current = next;
+                    }
+                    None => break false,
+                }
+            };
+            if chain.len() == 1 {
+                break;
+            }
+            if closed && chain.len() >= 3 {
+                loops.push(chain);
+            } else {
+                all_closed = false;
+            }
+        }
+    }
+
+    let loops = loops
         .into_iter()
-        .map(|vertex| {
-            let v2 = [dot_v3(vertex.pos(), plane_u), dot_v3(vertex.pos(), plane_v)];
-            minx = minx.min(v2[0]);
-            miny = miny.min(v2[1]);
-            maxx = maxx.max(v2[0]);
-            maxy = maxy.max(v2[1]);
-            (vertex, v2)
+        .map(|chain| chain.into_iter().map(|idx| points[idx].clone()).collect())
+        .collect();
+    (loops, all_closed)
+}
+
+fn cross_2d<S: Scalar>(a: [S; 2], b: [S; 2], c: [S; 2]) -> S {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Twice the signed area of the polygon, positive for counter clockwise winding.
+fn signed_area<S: Scalar>(polygon: &[[S; 2]]) -> S {
+    let mut area = S::ZERO;
+    let mut prev = match polygon.last() {
+        Some(&last) => last,
+        None => return area,
+    };
+    for &point in polygon {
+        area += prev[0] * point[1] - point[0] * prev[1];
+        prev = point;
+    }
+    area
+}
+
+fn in_triangle<S: Scalar>(p: [S; 2], a: [S; 2], b: [S; 2], c: [S; 2]) -> bool {
+    cross_2d(a, b, p) >= S::ZERO && cross_2d(b, c, p) >= S::ZERO && cross_2d(c, a, p) >= S::ZERO
+}
+
+/// Ray casting test whether the point lies inside of the polygon.
+fn in_polygon<S: Scalar>(p: [S; 2], points: &[[S; 2]], polygon: &[usize]) -> bool {
+    let mut inside = false;
+    let mut prev = match polygon.last() {
+        Some(&last) => points[last],
+        None => return inside,
+    };
+    for &idx in polygon {
+        let curr = points[idx];
+        if (curr[1] > p[1]) != (prev[1] > p[1])
+            && p[0] < (prev[0] - curr[0]) * (p[1] - curr[1]) / (prev[1] - curr[1]) + curr[0]
+        {
+            inside = !inside;
+        }
+        prev = curr;
+    }
+    inside
+}
+
+/// Merges a clockwise hole into the counter clockwise outer polygon by connecting the two with a
+/// bridge of two coinciding edges, leaving a single (weakly) simple polygon.
+fn bridge_hole<S: Scalar>(points: &[[S; 2]], outer: &mut Vec<usize>, hole: &[usize]) {
+    // the rightmost point of the hole is guaranteed to see some part of the outer polygon to its right
+    let (hole_start, m) = hole
+        .iter()
+        .enumerate()
+        .map(|(i, &idx)| (i, points[idx]))
+        .fold(
+            (0, [S::MIN; 2]),
+            |acc, it| {
+                if it.1[0] > acc.1[0] {
+                    it
+                } else {
+                    acc
+                }
+            },
+        );
+
+    // cast a ray towards +x and find the closest edge of the outer polygon it hits
+    let len = outer.len();
+    let mut hit = None;
+    let mut hit_x = S::MAX;
+    for i in 0..len {
+        let a = points[outer[i]];
+        let b = points[outer[(i + 1) % len]];
+        if (a[1] <= m[1]) == (b[1] <= m[1]) || a[1] == b[1] {
+            continue;
+        }
+        let x = a[0] + (m[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+        if x >= m[0] && x < hit_x {
+            hit_x = x;
+            hit = Some(if a[0] > b[0] { i } else { (i + 1) % len });
+        }
+    }
+
+    let bridge = match hit {
+        Some(candidate) => {
+            // the endpoint of the hit edge might be occluded by reflex vertices of the outer polygon,
+            // in which case the one with the smallest angle to the ray is visible instead
+            let p = points[outer[candidate]];
+            let i = [hit_x, m[1]];
+            let (t0, t1, t2) = if m[1] < p[1] { (m, i, p) } else { (m, p, i) };
+            (0..len)
+                .filter(|&k| k != candidate)
+                .filter(|&k| {
+                    let prev = points[outer[(k + len - 1) % len]];
+                    let next = points[outer[(k + 1) % len]];
+                    let q = points[outer[k]];
+                    q != m && cross_2d(prev, q, next) <= S::ZERO && in_triangle(q, t0, t1, t2)
+                })
+                .map(|k| {
+                    let q = points[outer[k]];
+                    let (dx, dy) = (q[0] - m[0], q[1] - m[1]);
+                    (k, dy.abs() / dx, dx * dx + dy * dy)
+                })
+                .fold(None, |best: Option<(usize, S, S)>, it| match best {
+                    Some(best) if (best.1, best.2) <= (it.1, it.2) => Some(best),
+                    _ => Some(it),
+                })
+                .map_or(candidate, |(k, ..)| k)
+        }
+        // degenerate outer polygon, fall back to connecting the closest vertex
+        None => {
+            (0..len)
+                .map(|k| {
+                    let q = points[outer[k]];
+                    (
+                        k,
+                        (q[0] - m[0]) * (q[0] - m[0]) + (q[1] - m[1]) * (q[1] - m[1]),
+                    )
+                })
+                .fold((0, S::MAX), |acc, it| if it.1 < acc.1 { it } else { acc })
+                .0
+        }
+    };
+
+    let splice = hole[hole_start..]
+        .iter()
+        .chain(&hole[..=hole_start])
+        .copied()
+        .chain(std::iter::once(outer[bridge]))
+        .collect::<Vec<_>>();
+    outer.splice(bridge + 1..bridge + 1, splice);
+}
+
+/// Buckets the reflex corners of a polygon into a grid, so ears only have to be tested against the
+/// corners close to them.
+struct CornerGrid<S> {
+    min: [S; 2],
+    scale: [S; 2],
+    size: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl<S: Scalar> CornerGrid<S> {
+    fn new(corners: &[usize], pos: impl Fn(usize) -> [S; 2]) -> Self {
+        let size = ((corners.len() as f64).sqrt().ceil() as usize).max(1);
+        let (mut min, mut max) = ([S::MAX; 2], [S::MIN; 2]);
+        for &i in corners {
+            let p = pos(i);
+            min = [min[0].min(p[0]), min[1].min(p[1])];
+            max = [max[0].max(p[0]), max[1].max(p[1])];
+        }
+        let scale = |extent: S| {
+            if extent > S::ZERO {
+                S::from_f64(size as f64) / extent
+            } else {
+                S::ZERO
+            }
+        };
+        let mut grid = CornerGrid {
+            min,
+            scale: [scale(max[0] - min[0]), scale(max[1] - min[1])],
+            size,
+            cells: vec![vec![]; size * size],
+        };
+        for &i in corners {
+            let [x, y] = grid.cell(pos(i));
+            grid.cells[y * size + x].push(i);
+        }
+        grid
+    }
+
+    /// The cell containing the point, clamped to the grid.
+    fn cell(&self, p: [S; 2]) -> [usize; 2] {
+        let axis = |i: usize| {
+            let cell = ((p[i] - self.min[i]) * self.scale[i]).to_f64();
+            (cell.max(0.0) as usize).min(self.size - 1)
+        };
+        [axis(0), axis(1)]
+    }
+
+    /// The corners in the cells overlapping the box.
+    fn query(&self, min: [S; 2], max: [S; 2]) -> impl Iterator<Item = usize> + '_ {
+        let ([x0, y0], [x1, y1]) = (self.cell(min), self.cell(max));
+        (y0..=y1)
+            .flat_map(move |y| (x0..=x1).map(move |x| y * self.size + x))
+            .flat_map(move |cell| self.cells[cell].iter().copied())
+    }
+}
+
+/// Triangulates a counter clockwise polygon by ear clipping, returning the indices of the triangles.
+fn ear_clip<S: Scalar>(points: &[[S; 2]], polygon: Vec<usize>) -> Vec<[usize; 3]> {
+    let len = polygon.len();
+    let mut triangles = Vec::with_capacity(len.saturating_sub(2));
+    if len < 3 {
+        return triangles;
+    }
+
+    // the remaining corners form a doubly linked list, clipping an ear unlinks its corner
+    let mut prev = (0..len).map(|i| (i + len - 1) % len).collect::<Vec<_>>();
+    let mut next = (0..len).map(|i| (i + 1) % len).collect::<Vec<_>>();
+    let corner = |i: usize| points[polygon[i]];
+    let is_convex = |prev: &[usize], next: &[usize], i: usize| {
+        cross_2d(corner(prev[i]), corner(i), corner(next[i])) > S::ZERO
+    };
+    let reflex_corners = |prev: &[usize], next: &[usize], start: usize| {
+        let mut reflex = vec![false; len];
+        let mut i = start;
+        loop {
+            reflex[i] = !is_convex(prev, next, i);
+            i = next[i];
+            if i == start {
+                break reflex;
+            }
+        }
+    };
+    // only reflex corners can lie inside of an ear, and clipping an ear never turns a corner reflex,
+    // so corners only ever have to be dropped from the grid, which they are by clearing their flag
+    let mut reflex = reflex_corners(&prev, &next, 0);
+    let grid_of = |reflex: &[bool]| {
+        let corners = (0..len).filter(|&i| reflex[i]).collect::<Vec<_>>();
+        CornerGrid::new(&corners, corner)
+    };
+    let mut grid = grid_of(&reflex);
+
+    let mut remaining = len;
+    let mut curr = 0;
+    let mut tried = 0;
+    while remaining > 3 {
+        let (p, n) = (prev[curr], next[curr]);
+        let (a, b, c) = (corner(p), corner(curr), corner(n));
+        let min = [a[0].min(b[0]).min(c[0]), a[1].min(b[1]).min(c[1])];
+        let max = [a[0].max(b[0]).max(c[0]), a[1].max(b[1]).max(c[1])];
+        // reflex or degenerate corners are no ears
+        let ear = cross_2d(a, b, c) > S::ZERO
+            && !grid.query(min, max).any(|other| {
+                let q = corner(other);
+                // points coinciding with a corner of the ear dont obstruct it, this happens at hole bridges
+                reflex[other] && q != a && q != b && q != c && in_triangle(q, a, b, c)
+            });
+        if !ear && tried < remaining {
+            tried += 1;
+            curr = n;
+            continue;
+        }
+
+        // the polygon is degenerate if there is no ear left, clip anyways to make progress
+        if ear {
+            triangles.push([polygon[p], polygon[curr], polygon[n]]);
+        }
+        next[p] = n;
+        prev[n] = p;
+        remaining -= 1;
+        tried = 0;
+        if ear {
+            reflex[curr] = false;
+            reflex[p] &= !is_convex(&prev, &next, p);
+            reflex[n] &= !is_convex(&prev, &next, n);
+            curr = n;
+        } else {
+            curr = n;
+            reflex = reflex_corners(&prev, &next, curr);
+            grid = grid_of(&reflex);
+        }
+    }
+    let (a, b, c) = (prev[curr], curr, next[curr]);
+    if cross_2d(corner(a), corner(b), corner(c)) > S::ZERO {
+        triangles.push([polygon[a], polygon[b], polygon[c]]);
+    }
+    triangles
+}
+
+/// Sorts the loops into polygons with holes by their nesting depth, loops at an even depth are outer
+/// boundaries while loops at an odd depth are holes of the loop directly containing them.
+///
+/// Returns the polygons with their holes merged in, wound counter clockwise.
+fn nest_loops<S: Scalar>(points: &[[S; 2]], mut loops: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    let containers = loops
+        .iter()
+        .enumerate()
+        .map(|(i, inner)| {
+            let p = points[inner[0]];
+            (0..loops.len())
+                .filter(|&j| j != i && in_polygon(p, points, &loops[j]))
+                .collect::<Vec<_>>()
         })
         .collect::<Vec<_>>();
-    (
-        BoundingBox {
-            x: minx,
-            y: miny,
-            width: maxx - minx,
-            height: maxy - miny,
-        },
-        mapped,
-    )
-}
-
-/// generate the cross section mesh from the intersection points twice, for each side
+    let depth = |i: usize| containers[i].len();
+
+    for (i, polygon) in loops.iter_mut().enumerate() {
+        let area = signed_area(&polygon.iter().map(|&idx| points[idx]).collect::<Vec<_>>());
+        // outer boundaries are wound counter clockwise, holes clockwise
+        if (area < S::ZERO) == (depth(i) % 2 == 0) {
+            polygon.reverse();
+        }
+    }
+
+    let mut holes = vec![vec![]; loops.len()];
+    for i in (0..loops.len()).filter(|&i| depth(i) % 2 == 1) {
+        if let Some(&parent) = containers[i].iter().find(|&&j| depth(j) + 1 == depth(i)) {
+            holes[parent].push(i);
+        }
+    }
+
+    let max_x = |polygon: &[usize]| {
+        polygon
+            .iter()
+            .map(|&idx| points[idx][0])
+            .fold(S::MIN, S::max)
+    };
+    (0..loops.len())
+        .filter(|&i| depth(i) % 2 == 0)
+        .map(|i| {
+            let mut outer = loops[i].clone();
+            let mut holes = holes[i].iter().map(|&h| &loops[h][..]).collect::<Vec<_>>();
+            // bridging from right to left keeps the bridges from crossing each other
+            holes.sort_by(|a, b| {
+                max_x(b)
+                    .partial_cmp(&max_x(a))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            for hole in holes {
+                bridge_hole(points, &mut outer, hole);
+            }
+            outer
+        })
+        .collect()
+}
+
+/// generate the cross section mesh from the cut segments twice, for each side
+///
+/// The segments are chained into closed loops which are triangulated as polygons with holes, which
+/// makes this work for concave and hollow cross sections as well.
 #[allow(clippy::type_complexity)]
-pub fn triangulate<V: Vertex + Clone>(
-    vertices: Vec<V>,
-    plane: Plane,
-    tb: &TextureBounds,
+pub fn triangulate_segments<S: Scalar, V: Vertex<S> + Clone>(
+    segments: Vec<[V; 2]>,
+    plane: Plane<S>,
+    tb: &TextureBounds<S>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
 ) -> Option<(Vec<Triangle<V>>, Vec<Triangle<V>>)> {
-    if vertices.len() < 3 {
+    let cap = triangulate_loops(segments, V::pos, plane, tb, tolerance);
+    if cap.polygons.is_empty() {
         return None;
     }
+    let lower_cross = cap_triangles(&cap, plane, Side::Below, ctor);
+    let upper_cross = cap_triangles(&cap, plane, Side::Above, ctor);
+    Some((
+        lower_cross.into_iter().flatten().collect(),
+        upper_cross.into_iter().flatten().collect(),
+    ))
+}
 
-    let plane_normal = plane.normal();
-    let neg_plane_normal = negate_v3(plane.normal());
-    let (bounding_box, mapped) = map_to_2d_with_bb(plane, vertices);
-
-    let mut hull = monotone_chain(mapped);
-
-    let mut lower_cross = Vec::with_capacity(hull.len() - 2);
-    let mut upper_cross = Vec::with_capacity(hull.len() - 2);
-
-    let BoundingBox {
-        x,
-        y,
-        width,
-        height,
-    } = bounding_box;
-    let max = [width, height];
-    let min = [x, y];
-    let tb_map = tb.mapper();
-    let (c, uvc) = {
-        let (v, uv) = hull.pop().unwrap();
-        (v.pos(), tb_map(div_v2(sub_v2(uv, min), max)))
+/// Like [`triangulate_segments`], but fails if the cut cant be triangulated completely.
+#[allow(clippy::type_complexity)]
+pub fn try_triangulate_segments<S: Scalar, V: Vertex<S> + Clone>(
+    segments: Vec<[V; 2]>,
+    plane: Plane<S>,
+    tb: &TextureBounds<S>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Result<(Vec<Triangle<V>>, Vec<Triangle<V>>), SliceError> {
+    let cap = triangulate_loops(segments, V::pos, plane, tb, tolerance);
+    if cap.degenerate {
+        return Err(SliceError::DegenerateCap);
+    }
+    let lower_cross = cap_triangles(&cap, plane, Side::Below, ctor);
+    let upper_cross = cap_triangles(&cap, plane, Side::Above, ctor);
+    Ok((
+        lower_cross.into_iter().flatten().collect(),
+        upper_cross.into_iter().flatten().collect(),
+    ))
+}
+
+/// Like [`triangulate_segments`], but keeps the triangles of each polygon of the cross section apart.
+#[allow(clippy::type_complexity)]
+pub fn triangulate_caps<S: Scalar, V: Vertex<S> + Clone>(
+    segments: Vec<[V; 2]>,
+    plane: Plane<S>,
+    tb: &TextureBounds<S>,
+    tolerance: Tolerance<S>,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Vec<(Vec<Triangle<V>>, Vec<Triangle<V>>)> {
+    let cap = triangulate_loops(segments, V::pos, plane, tb, tolerance);
+    let lower_cross = cap_triangles(&cap, plane, Side::Below, ctor);
+    let upper_cross = cap_triangles(&cap, plane, Side::Above, ctor);
+    lower_cross.into_iter().zip(upper_cross).collect()
+}
+
+/// Like [`triangulate_segments`], but only generates the cross section of one side.
+pub fn triangulate_side<S: Scalar, V: Vertex<S> + Clone>(
+    segments: Vec<[V; 2]>,
+    plane: Plane<S>,
+    tb: &TextureBounds<S>,
+    tolerance: Tolerance<S>,
+    side: Side,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Vec<Triangle<V>> {
+    let cap = triangulate_loops(segments, V::pos, plane, tb, tolerance);
+    cap_triangles(&cap, plane, side, ctor)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Builds the cross section triangles closing the hull on the given side of the plane, one list per
+/// polygon.
+///
+/// Every cap vertex is only created once, no matter how many triangles share it.
+fn cap_triangles<S: Scalar, V: Vertex<S> + Clone>(
+    cap: &Cap<V, S>,
+    plane: Plane<S>,
+    side: Side,
+    ctor: &mut impl VertexConstructor<V, S>,
+) -> Vec<Vec<Triangle<V>>> {
+    let Cap {
+        points,
+        uvs,
+        tangent,
+        bitangent,
+        polygons,
+        ..
+    } = cap;
+    let normal = match side {
+        Side::Below => negate_v3(plane.normal()),
+        _ => plane.normal(),
+    };
+    let mut vertices = vec![None; points.len()];
+    let mut vertex = |idx: usize| {
+        vertices[idx]
+            .get_or_insert_with(|| {
+                let attributes = CapAttributes {
+                    uv: uvs[idx],
+                    normal,
+                    tangent: *tangent,
+                    bitangent: *bitangent,
+                };
+                ctor.cap_vertex(&points[idx], plane, attributes)
+            })
+            .clone()
     };
-    // FIXME: const generic slice functions
-    for vertices in hull.windows(2) {
-        let &(ref a, uva) = &vertices[0];
-        let &(ref b, uvb) = &vertices[1];
-        let (a, uva) = (a.pos(), tb_map(div_v2(sub_v2(uva, min), max)));
-        let (b, uvb) = (b.pos(), tb_map(div_v2(sub_v2(uvb, min), max)));
-        upper_cross.push(Triangle::new(
-            V::new(a, uva, plane_normal),
-            V::new(b, uvb, plane_normal),
-            V::new(c, uvc, plane_normal),
-        ));
-        // reversed winding order and normal
-        lower_cross.push(Triangle::new(
-            V::new(a, uva, neg_plane_normal),
-            V::new(c, uvc, neg_plane_normal),
-            V::new(b, uvb, neg_plane_normal),
-        ));
-    }
-
-    Some((lower_cross, upper_cross))
+    polygons
+        .iter()
+        .map(|triangles| {
+            triangles
+                .iter()
+                .map(|&[a, b, c]| {
+                    let (a, b, c) = (vertex(a), vertex(b), vertex(c));
+                    match side {
+                        // reversed winding order and normal
+                        Side::Below => Triangle::new(a, c, b),
+                        _ => Triangle::new(a, b, c),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A triangulated cross section.
+pub(crate) struct Cap<T, S> {
+    /// the welded points of the cut
+    pub points: Vec<T>,
+    /// the texture coordinates of each point
+    pub uvs: Vec<[S; 2]>,
+    /// the direction along which the u texture coordinate grows
+    pub tangent: [S; 3],
+    /// the direction along which the v texture coordinate grows
+    pub bitangent: [S; 3],
+    /// the triangles of each polygon, indexing into `points`, wound for the upper side
+    pub polygons: Vec<Vec<[usize; 3]>>,
+    /// whether parts of the cut could not be triangulated
+    pub degenerate: bool,
+}
+
+/// Chains the cut segments into closed loops and triangulates them as polygons with holes.
+pub(crate) fn triangulate_loops<S: Scalar, T: Clone>(
+    segments: Vec<[T; 2]>,
+    pos: impl Fn(&T) -> [S; 3],
+    plane: Plane<S>,
+    tb: &TextureBounds<S>,
+    tolerance: Tolerance<S>,
+) -> Cap<T, S> {
+    let (loops, all_closed) = chain_loops(segments, &pos, tolerance);
+    let mut degenerate = !all_closed || loops.is_empty();
+
+    let mut offset = 0;
+    let loops_idx = loops
+        .iter()
+        .map(|l| {
+            offset += l.len();
+            (offset - l.len()..offset).collect()
+        })
+        .collect();
+    let (points, mapped): (Vec<_>, Vec<_>) =
+        map_to_2d(plane, loops.into_iter().flatten(), &pos).unzip();
+
+    let uv_map = UvMap::new(plane, tb, points.iter().map(&pos));
+    let uvs = points.iter().map(|p| uv_map.uv(pos(p))).collect();
+    let (tangent, bitangent) = uv_map.frame(plane.normal());
+
+    let polygons = nest_loops(&mapped, loops_idx)
+        .into_iter()
+        .map(|polygon| ear_clip(&mapped, polygon))
+        .filter(|triangles| {
+            degenerate |= triangles.is_empty();
+            !triangles.is_empty()
+        })
+        .collect();
+
+    Cap {
+        points,
+        uvs,
+        tangent,
+        bitangent,
+        polygons,
+        degenerate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::dot_v3;
+    use crate::testing::{area, assert_close, cube, face_normal, face_uv, volume, V};
+    use crate::{slice, Plane, SliceOptions, SliceResult, SubMesh, TextureBounds, Triangle};
+
+    /// Extrudes the `#` cells of the mask into unit boxes between `z = -1` and `z = 1`, leaving out
+    /// the walls between neighbouring cells so the result is one closed, flat shaded hull.
+    fn extrude(mask: &[&str]) -> Vec<Triangle<V>> {
+        let filled = |x: i32, y: i32| {
+            y >= 0
+                && x >= 0
+                && mask
+                    .get(y as usize)
+                    .and_then(|row| row.as_bytes().get(x as usize))
+                    == Some(&b'#')
+        };
+        let mut triangles = vec![];
+        let mut quad = |corners: [[f32; 3]; 4], normal: [f32; 3]| {
+            let vertex = |pos| V {
+                pos,
+                normal,
+                uv: face_uv(normal),
+            };
+            let [a, b, c, d] = corners;
+            let mut pair = [
+                Triangle::new(vertex(a), vertex(b), vertex(c)),
+                Triangle::new(vertex(a), vertex(c), vertex(d)),
+            ];
+            if dot_v3(face_normal(&pair[0]), normal) < 0.0 {
+                for t in &mut pair {
+                    std::mem::swap(&mut t.b, &mut t.c);
+                }
+            }
+            triangles.extend(pair.iter().cloned());
+        };
+        for (y, row) in mask.iter().enumerate() {
+            for x in (0..row.len()).filter(|&x| filled(x as i32, y as i32)) {
+                let (x0, y0) = (x as f32, y as f32);
+                let (x1, y1) = (x0 + 1.0, y0 + 1.0);
+                for &z in &[-1.0, 1.0] {
+                    quad(
+                        [[x0, y0, z], [x1, y0, z], [x1, y1, z], [x0, y1, z]],
+                        [0.0, 0.0, z],
+                    );
+                }
+                let (x, y) = (x as i32, y as i32);
+                if !filled(x - 1, y) {
+                    quad(
+                        [[x0, y0, -1.0], [x0, y1, -1.0], [x0, y1, 1.0], [x0, y0, 1.0]],
+                        [-1.0, 0.0, 0.0],
+                    );
+                }
+                if !filled(x + 1, y) {
+                    quad(
+                        [[x1, y0, -1.0], [x1, y1, -1.0], [x1, y1, 1.0], [x1, y0, 1.0]],
+                        [1.0, 0.0, 0.0],
+                    );
+                }
+                if !filled(x, y - 1) {
+                    quad(
+                        [[x0, y0, -1.0], [x1, y0, -1.0], [x1, y0, 1.0], [x0, y0, 1.0]],
+                        [0.0, -1.0, 0.0],
+                    );
+                }
+                if !filled(x, y + 1) {
+                    quad(
+                        [[x0, y1, -1.0], [x1, y1, -1.0], [x1, y1, 1.0], [x0, y1, 1.0]],
+                        [0.0, 1.0, 0.0],
+                    );
+                }
+            }
+        }
+        triangles
+    }
+
+    /// Every vertex carries the normal and texture coordinates of the face it belongs to.
+    fn assert_flat_shaded(triangles: &[Triangle<V>]) {
+        for t in triangles {
+            let normal = face_normal(t);
+            for v in &[&t.a, &t.b, &t.c] {
+                assert!(
+                    dot_v3(v.normal, normal) > 0.999,
+                    "{:?} on face {:?}",
+                    v.normal,
+                    normal
+                );
+            }
+        }
+    }
+
+    /// Slices the mesh along `z = height` and checks both halves against the expected cross section.
+    fn assert_sliced(triangles: Vec<Triangle<V>>, height: f32, cap_area: f32) -> [SubMesh<V>; 2] {
+        let plane = Plane::from_pos_normal([0.0, 0.0, height], [0.0, 0.0, 1.0]);
+        let (upper, lower) = match slice(
+            triangles,
+            plane,
+            TextureBounds::default(),
+            SliceOptions::default(),
+        ) {
+            SliceResult::Sliced(upper, lower) => (upper, lower),
+            _ => panic!("the plane cuts through the mesh"),
+        };
+        assert_close(area(&upper.cross_section), cap_area);
+        assert_close(area(&lower.cross_section), cap_area);
+        assert_close(volume(&upper), cap_area * (1.0 - height));
+        assert_close(volume(&lower), cap_area * (1.0 + height));
+        // the caps face away from their half, but carry the normal of the side they belong to
+        for (mesh, normal) in [(&upper, [0.0, 0.0, 1.0]), (&lower, [0.0, 0.0, -1.0])].iter() {
+            assert_flat_shaded(&mesh.hull);
+            for t in &mesh.cross_section {
+                assert!(dot_v3(face_normal(t), *normal) < -0.999);
+                for v in &[&t.a, &t.b, &t.c] {
+                    assert_eq!(v.normal, *normal);
+                }
+            }
+        }
+        [upper, lower]
+    }
+
+    #[test]
+    fn annulus() {
+        assert_sliced(extrude(&["###", "#.#", "###"]), 0.0, 8.0);
+    }
+
+    #[test]
+    fn multiple_holes() {
+        assert_sliced(extrude(&["#####", "#.#.#", "#####"]), 0.25, 13.0);
+    }
+
+    #[test]
+    fn island_in_hole() {
+        let mask = ["#####", "#...#", "#.#.#", "#...#", "#####"];
+        assert_sliced(extrude(&mask), -0.5, 17.0);
+    }
+
+    #[test]
+    fn nested_rings() {
+        let mask = [
+            "#######", "#.....#", "#.###.#", "#.#.#.#", "#.###.#", "#.....#", "#######",
+        ];
+        assert_sliced(extrude(&mask), 0.0, 24.0 + 8.0);
+    }
+
+    #[test]
+    fn flat_shaded_cube() {
+        for mesh in assert_sliced(cube(), 0.3, 4.0).iter() {
+            for t in &mesh.hull {
+                let uv = face_uv(face_normal(t));
+                for v in &[&t.a, &t.b, &t.c] {
+                    assert_close(v.uv[0], uv[0]);
+                    assert_close(v.uv[1], uv[1]);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,164 @@
+use crate::math::{dot_v3, lerp3, magnitude_squared, nlerp3, normalized, scale_v3, sub_v3};
+use crate::triangulate::plane_basis;
+use crate::{CapAttributes, Plane, Scalar, Vertex};
+
+/// A vertex only made of a position.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PosVertex<S = f32> {
+    pub pos: [S; 3],
+}
+
+impl<S: Scalar> Vertex<S> for PosVertex<S> {
+    #[inline]
+    fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+        PosVertex {
+            pos: lerp3(a.pos, b.pos, t),
+        }
+    }
+
+    #[inline]
+    fn new(pos: [S; 3], _: [S; 2], _: [S; 3]) -> Self {
+        PosVertex { pos }
+    }
+
+    #[inline]
+    fn pos(&self) -> [S; 3] {
+        self.pos
+    }
+}
+
+/// A vertex with a position and a unit normal.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PosNormalVertex<S = f32> {
+    pub pos: [S; 3],
+    pub normal: [S; 3],
+}
+
+impl<S: Scalar> Vertex<S> for PosNormalVertex<S> {
+    #[inline]
+    fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+        PosNormalVertex {
+            pos: lerp3(a.pos, b.pos, t),
+            normal: nlerp3(a.normal, b.normal, t),
+        }
+    }
+
+    #[inline]
+    fn new(pos: [S; 3], _: [S; 2], normal: [S; 3]) -> Self {
+        PosNormalVertex { pos, normal }
+    }
+
+    #[inline]
+    fn pos(&self) -> [S; 3] {
+        self.pos
+    }
+}
+
+/// A vertex with a position, a unit normal and texture coordinates.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PosNormalUvVertex<S = f32> {
+    pub pos: [S; 3],
+    pub normal: [S; 3],
+    pub uv: [S; 2],
+}
+
+impl<S: Scalar> Vertex<S> for PosNormalUvVertex<S> {
+    #[inline]
+    fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+        PosNormalUvVertex {
+            pos: lerp3(a.pos, b.pos, t),
+            normal: nlerp3(a.normal, b.normal, t),
+            uv: lerp_uv(a.uv, b.uv, t),
+        }
+    }
+
+    #[inline]
+    fn new(pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> Self {
+        PosNormalUvVertex { pos, normal, uv }
+    }
+
+    #[inline]
+    fn pos(&self) -> [S; 3] {
+        self.pos
+    }
+}
+
+/// A vertex with a position, a unit normal, texture coordinates and a tangent.
+///
+/// The tangent is a unit vector perpendicular to the normal, its `w` component holds the handedness
+/// of the tangent space, that is the bitangent is `cross(normal, tangent) * w`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PosNormalUvTangentVertex<S = f32> {
+    pub pos: [S; 3],
+    pub normal: [S; 3],
+    pub uv: [S; 2],
+    pub tangent: [S; 4],
+}
+
+impl<S: Scalar> Vertex<S> for PosNormalUvTangentVertex<S> {
+    fn new_interpolated(a: &Self, b: &Self, t: S) -> Self {
+        let normal = nlerp3(a.normal, b.normal, t);
+        let [ax, ay, az, aw] = a.tangent;
+        let [bx, by, bz, bw] = b.tangent;
+        // the interpolated tangent is no longer perpendicular to the interpolated normal, so project
+        // it back onto the tangent plane
+        let tangent = lerp3([ax, ay, az], [bx, by, bz], t);
+        let tangent = sub_v3(tangent, scale_v3(normal, dot_v3(normal, tangent)));
+        let tangent = if magnitude_squared(tangent).is_normal() {
+            normalized(tangent)
+        } else {
+            tangent
+        };
+        let handedness = if aw + t * (bw - aw) < S::ZERO {
+            -S::ONE
+        } else {
+            S::ONE
+        };
+        PosNormalUvTangentVertex {
+            pos: lerp3(a.pos, b.pos, t),
+            normal,
+            uv: lerp_uv(a.uv, b.uv, t),
+            tangent: [tangent[0], tangent[1], tangent[2], handedness],
+        }
+    }
+
+    /// Only used if the tangent frame of the cap is unknown, the tangent points along the u axis of
+    /// the upper cap's texture coordinates for the default texture bounds.
+    fn new(pos: [S; 3], uv: [S; 2], normal: [S; 3]) -> Self {
+        let (plane_u, _) = plane_basis(Plane::new(normal, S::ZERO));
+        // the v axis of the caps is cross(plane_u, normal), so the tangent space is left handed
+        let [x, y, z] = normalized(plane_u);
+        PosNormalUvTangentVertex {
+            pos,
+            normal,
+            uv,
+            tangent: [x, y, z, -S::ONE],
+        }
+    }
+
+    /// The tangent points along the u axis of the cap's texture coordinates.
+    #[inline]
+    fn new_cap(pos: [S; 3], attributes: CapAttributes<S>) -> Self {
+        let [x, y, z] = attributes.tangent;
+        PosNormalUvTangentVertex {
+            pos,
+            normal: attributes.normal,
+            uv: attributes.uv,
+            tangent: [x, y, z, attributes.handedness()],
+        }
+    }
+
+    #[inline]
+    fn pos(&self) -> [S; 3] {
+        self.pos
+    }
+}
+
+#[inline]
+fn lerp_uv<S: Scalar>([u, v]: [S; 2], [u2, v2]: [S; 2], t: S) -> [S; 2] {
+    [u + t * (u2 - u), v + t * (v2 - v)]
+}